@@ -0,0 +1,56 @@
+// command line helpers shared by the client, server and editor
+
+use std::str::FromStr;
+
+use tracing::warn;
+
+// returns the value after a flag like --name <name>
+pub fn arg_value(args: &[String], flag: &str) -> Option<String> {
+    args.iter().position(|arg| arg == flag).and_then(|index| args.get(index + 1).cloned())
+}
+
+// the value after a flag parsed as T. a typo like --volume loud gets a warning and None so the caller uses its default instead of crashing
+pub fn parse_arg<T: FromStr>(args: &[String], flag: &str) -> Option<T> {
+
+    let value = arg_value(args, flag)?;
+
+    match value.parse() {
+        Ok(parsed) => Some(parsed),
+        Err(_) => {
+            warn!(flag, %value, "couldnt read command line value, ignoring it");
+
+            None
+        },
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn args(args: &[&str]) -> Vec<String> {
+        args.iter().map(|arg| arg.to_string()).collect()
+    }
+
+    #[test]
+    fn finds_the_value_after_a_flag() {
+
+        let args = args(&["client", "--name", "kruz", "--spectate"]);
+
+        assert_eq!(arg_value(&args, "--name"), Some("kruz".to_string()));
+        assert_eq!(arg_value(&args, "--log-dir"), None);
+
+        // a flag at the end has no value
+        assert_eq!(arg_value(&args, "--spectate"), None);
+    }
+
+    #[test]
+    fn values_that_dont_parse_are_ignored() {
+
+        let args = args(&["server", "--max-players", "lots", "--console-port", "5557"]);
+
+        assert_eq!(parse_arg::<usize>(&args, "--max-players"), None);
+        assert_eq!(parse_arg::<u16>(&args, "--console-port"), Some(5557));
+        assert_eq!(parse_arg::<u16>(&args, "--level"), None);
+    }
+}
@@ -0,0 +1,341 @@
+use std::{collections::HashMap, path::{Path, PathBuf}, sync::mpsc::{channel, Receiver}};
+
+use gamelibrary::time::Time;
+use macroquad::{audio::{load_sound_from_bytes, Sound}, color::{BLACK, DARKGRAY, WHITE}, file::load_file, shapes::draw_rectangle, text::draw_text, texture::{load_image, FilterMode, Texture2D}, window::{clear_background, next_frame, screen_height, screen_width}};
+use notify::{Event, EventKind, RecommendedWatcher, RecursiveMode, Watcher};
+use tracing::{info, warn};
+
+use crate::sound::{panned_key, split_channels};
+
+pub const ASSETS_DIRECTORY: &str = "assets";
+
+// if this exists we load exactly what it lists instead of searching the assets folder
+pub const MANIFEST_PATH: &str = "assets/manifest.json";
+
+const TEXTURE_EXTENSIONS: [&str; 1] = ["png"];
+const SOUND_EXTENSIONS: [&str; 2] = ["wav", "ogg"];
+
+// how long a file has to go without changing before we reload it, so we dont read it while it is still being saved
+const RELOAD_DELAY_MILLISECONDS: i64 = 250;
+// a file that still fails to load after this many tries is probably actually broken
+const MAX_RELOAD_ATTEMPTS: u32 = 5;
+
+#[derive(Clone, Copy, PartialEq)]
+pub enum AssetKind {
+    Texture,
+    Sound
+}
+
+impl AssetKind {
+    pub fn from_path(path: &str) -> Option<Self> {
+
+        let extension = Path::new(path).extension()?.to_str()?.to_lowercase();
+
+        if TEXTURE_EXTENSIONS.contains(&extension.as_str()) {
+            return Some(AssetKind::Texture)
+        }
+
+        if SOUND_EXTENSIONS.contains(&extension.as_str()) {
+            return Some(AssetKind::Sound)
+        }
+
+        None
+    }
+}
+
+// loads everything under assets/ into the texture and sound maps the TickContext hands out
+// assets are keyed by their path with forward slashes, like "assets/player/idle.png"
+pub struct AssetManager {
+    pub pending: Vec<(String, AssetKind)>,
+    pub total: usize,
+    // the key and the reason for every asset that couldnt be loaded
+    pub failed: Vec<(String, String)>
+}
+
+impl AssetManager {
+
+    pub fn new(keys: Vec<String>) -> Self {
+
+        let mut pending: Vec<(String, AssetKind)> = keys.into_iter()
+            .filter_map(|key| {
+                let kind = AssetKind::from_path(&key)?;
+
+                Some((key, kind))
+            })
+            .collect();
+
+        // we pop from the back so reverse to load in alphabetical order
+        pending.sort_by(|a, b| b.0.cmp(&a.0));
+
+        Self {
+            total: pending.len(),
+            pending,
+            failed: vec![],
+        }
+    }
+
+    // use the manifest if there is one, otherwise look through the assets folder ourselves
+    pub fn discover() -> Self {
+
+        if let Ok(manifest) = std::fs::read_to_string(MANIFEST_PATH) {
+            match serde_json::from_str::<Vec<String>>(&manifest) {
+                Ok(keys) => {
+                    info!(path = MANIFEST_PATH, count = keys.len(), "using asset manifest");
+
+                    return Self::new(keys)
+                },
+                Err(error) => warn!(path = MANIFEST_PATH, %error, "failed to parse asset manifest, searching the assets folder instead"),
+            }
+        }
+
+        let mut keys = vec![];
+
+        find_assets(Path::new(ASSETS_DIRECTORY), &mut keys);
+
+        Self::new(keys)
+    }
+
+    pub fn is_done(&self) -> bool {
+        self.pending.is_empty()
+    }
+
+    // between 0 and 1
+    pub fn progress(&self) -> f32 {
+
+        if self.total == 0 {
+            return 1.
+        }
+
+        (self.total - self.pending.len()) as f32 / self.total as f32
+    }
+
+    // loads a single asset so the caller can draw a frame in between
+    pub async fn load_next(&mut self, textures: &mut HashMap<String, Texture2D>, sounds: &mut HashMap<String, Sound>) {
+
+        let (key, kind) = match self.pending.pop() {
+            Some(asset) => asset,
+            None => return,
+        };
+
+        if let Err(error) = load_asset(&key, kind, textures, sounds).await {
+            warn!(%key, %error, "failed to load asset");
+
+            self.failed.push((key, error));
+        }
+    }
+
+    // loads everything while showing a progress bar. returns once every asset has been tried
+    pub async fn load_with_loading_screen(&mut self, textures: &mut HashMap<String, Texture2D>, sounds: &mut HashMap<String, Sound>) {
+
+        while !self.is_done() {
+
+            let current = self.pending.last().map(|(key, _)| key.clone()).unwrap_or_default();
+
+            draw_loading_screen(self.progress(), &current);
+
+            next_frame().await;
+
+            self.load_next(textures, sounds).await;
+        }
+
+        info!(textures = textures.len(), sounds = sounds.len(), failed = self.failed.len(), "finished loading assets");
+    }
+}
+
+// loads or reloads a single asset, replacing whatever was stored under its key
+// a half written file gives us an error here instead of a panic and the old version stays loaded
+pub async fn load_asset(key: &str, kind: AssetKind, textures: &mut HashMap<String, Texture2D>, sounds: &mut HashMap<String, Sound>) -> Result<(), String> {
+    match kind {
+        AssetKind::Texture => {
+            // load_texture panics on bad image data so decode it ourselves first
+            let image = load_image(key).await.map_err(|error| error.to_string())?;
+
+            let texture = Texture2D::from_image(&image);
+
+            // all of our art is pixel art so keep it crisp when it gets scaled up
+            texture.set_filter(FilterMode::Nearest);
+
+            textures.insert(key.to_string(), texture);
+        },
+        AssetKind::Sound => {
+            let bytes = load_file(key).await.map_err(|error| error.to_string())?;
+
+            let sound = load_sound_from_bytes(&bytes).await.map_err(|error| error.to_string())?;
+
+            sounds.insert(key.to_string(), sound);
+
+            // the copies that only play on one side, for panning
+            if let Some((left, right)) = split_channels(&bytes) {
+                sounds.insert(panned_key(key, false), load_sound_from_bytes(&left).await.map_err(|error| error.to_string())?);
+                sounds.insert(panned_key(key, true), load_sound_from_bytes(&right).await.map_err(|error| error.to_string())?);
+            }
+        },
+    }
+
+    Ok(())
+}
+
+// watches the assets folder and reloads anything that changes while the game is running
+// timelines are looked up from the texture keys every frame so new frames show up as soon as they are loaded
+pub struct AssetWatcher {
+    // dropping the watcher stops the events
+    pub watcher: RecommendedWatcher,
+    pub events: Receiver<notify::Result<Event>>,
+    pub root: PathBuf,
+    // keys waiting to be reloaded, with when they last changed and how many times we have tried
+    pub changed: HashMap<String, (Time, u32)>
+}
+
+impl AssetWatcher {
+
+    pub fn start() -> Result<Self, notify::Error> {
+
+        let (sender, events) = channel();
+
+        let mut watcher = notify::recommended_watcher(sender)?;
+
+        // events come back with absolute paths so we need this to turn them into keys
+        let root = std::fs::canonicalize(ASSETS_DIRECTORY)?;
+
+        watcher.watch(&root, RecursiveMode::Recursive)?;
+
+        info!(directory = %root.display(), "watching assets for changes");
+
+        Ok(Self {
+            watcher,
+            events,
+            root,
+            changed: HashMap::new(),
+        })
+    }
+
+    // turns an absolute path from the watcher into the key the asset was loaded under
+    pub fn key(&self, path: &Path) -> Option<String> {
+
+        let relative = path.strip_prefix(&self.root).ok()?;
+
+        Some(format!("{}/{}", ASSETS_DIRECTORY, relative.to_string_lossy().replace('\\', "/")))
+    }
+
+    fn receive_events(&mut self) {
+
+        while let Ok(event) = self.events.try_recv() {
+
+            let event = match event {
+                Ok(event) => event,
+                Err(error) => {
+                    warn!(%error, "asset watcher error");
+
+                    continue;
+                },
+            };
+
+            if !matches!(event.kind, EventKind::Create(_) | EventKind::Modify(_)) {
+                continue;
+            }
+
+            for path in event.paths {
+
+                let key = match self.key(&path) {
+                    Some(key) => key,
+                    None => continue,
+                };
+
+                if AssetKind::from_path(&key).is_none() {
+                    continue;
+                }
+
+                // every write pushes the reload back so we only read the file once the editor is done with it
+                self.changed.insert(key, (Time::now(), 0));
+            }
+        }
+    }
+
+    // call this once a frame
+    pub async fn reload_changed(&mut self, textures: &mut HashMap<String, Texture2D>, sounds: &mut HashMap<String, Sound>) {
+
+        self.receive_events();
+
+        let ready: Vec<String> = self.changed.iter()
+            .filter(|(_, (changed_at, _))| changed_at.elapsed().num_milliseconds() >= RELOAD_DELAY_MILLISECONDS)
+            .map(|(key, _)| key.clone())
+            .collect();
+
+        for key in ready {
+
+            let (_, attempts) = self.changed.remove(&key).unwrap();
+
+            let kind = match AssetKind::from_path(&key) {
+                Some(kind) => kind,
+                None => continue,
+            };
+
+            match load_asset(&key, kind, textures, sounds).await {
+                Ok(()) => info!(%key, "reloaded asset"),
+                Err(error) if attempts + 1 < MAX_RELOAD_ATTEMPTS => {
+                    // most likely the file is still being written, try again in a bit
+                    warn!(%key, %error, attempt = attempts + 1, "failed to reload asset, retrying");
+
+                    self.changed.insert(key, (Time::now(), attempts + 1));
+                },
+                Err(error) => warn!(%key, %error, "giving up reloading asset, keeping the old version"),
+            }
+        }
+    }
+}
+
+// collects the key of every loadable file under a directory
+fn find_assets(directory: &Path, keys: &mut Vec<String>) {
+
+    let entries = match std::fs::read_dir(directory) {
+        Ok(entries) => entries,
+        Err(error) => {
+            warn!(directory = %directory.display(), %error, "failed to read assets directory");
+
+            return;
+        },
+    };
+
+    for entry in entries.flatten() {
+
+        let path = entry.path();
+
+        if path.is_dir() {
+            find_assets(&path, keys);
+
+            continue;
+        }
+
+        // keys use forward slashes on every platform
+        let key = path.to_string_lossy().replace('\\', "/");
+
+        if AssetKind::from_path(&key).is_some() {
+            keys.push(key);
+        }
+    }
+}
+
+// animation timelines are folders of numbered frames: assets/player/timelines/walk/0.png, 1.png, ...
+// returns the keys of every loaded frame in order, stopping at the first gap
+pub fn timeline(textures: &HashMap<String, Texture2D>, directory: &str) -> Vec<String> {
+    (0..)
+        .map(|frame| format!("{}/{}.png", directory, frame))
+        .take_while(|key| textures.contains_key(key))
+        .collect()
+}
+
+pub fn draw_loading_screen(progress: f32, current: &str) {
+
+    clear_background(BLACK);
+
+    let bar_width = screen_width() / 2.;
+    let bar_x = (screen_width() - bar_width) / 2.;
+    let bar_y = screen_height() / 2.;
+
+    draw_text("loading assets...", bar_x, bar_y - 20., 30., WHITE);
+
+    draw_rectangle(bar_x, bar_y, bar_width, 20., DARKGRAY);
+    draw_rectangle(bar_x, bar_y, bar_width * progress, 20., WHITE);
+
+    draw_text(current, bar_x, bar_y + 45., 20., WHITE);
+}
@@ -0,0 +1,236 @@
+use gamelibrary::proxies::macroquad::math::vec2::Vec2;
+use macroquad::{camera::Camera2D, window::{screen_height, screen_width}};
+
+const MIN_ZOOM: f32 = 0.25;
+const MAX_ZOOM: f32 = 4.;
+// how much one notch of the mouse wheel zooms by
+const ZOOM_STEP: f32 = 1.1;
+// higher is snappier. this is how quickly the camera closes the gap to what it is following
+const FOLLOW_SPEED: f32 = 8.;
+// the most the screen will move in pixels at full shake
+const MAX_SHAKE_OFFSET: f32 = 20.;
+// how much shake wears off per second
+const SHAKE_DECAY: f32 = 1.5;
+
+// the world has y going up and the screen has y going down. everything that converts between the two should go through here
+pub struct Camera {
+    // the world position at the center of the screen
+    pub position: Vec2,
+    pub zoom: f32,
+    // between 0 and 1, goes back down to 0 by itself
+    pub shake: f32,
+    pub shake_offset: Vec2,
+    // the lowest and highest world positions the camera is allowed to show
+    pub bounds: Option<(Vec2, Vec2)>
+}
+
+impl Camera {
+
+    pub fn new(position: Vec2) -> Self {
+        Self {
+            position,
+            zoom: 1.,
+            shake: 0.,
+            shake_offset: Vec2::new(0., 0.),
+            bounds: None,
+        }
+    }
+
+    // the offset the draw functions expect. this is where the bottom left of the screen is in the world before zoom
+    pub fn offset(&self) -> Vec2 {
+        self.offset_for(screen_width(), screen_height())
+    }
+
+    // the conversions take the window size so they can be tested without a window
+    fn offset_for(&self, width: f32, height: f32) -> Vec2 {
+        Vec2::new(
+            self.position.x - width / 2. + self.shake_offset.x,
+            self.position.y - height / 2. + self.shake_offset.y
+        )
+    }
+
+    // move towards the target a bit every frame instead of snapping to it
+    pub fn follow(&mut self, target: &Vec2, delta: f32) {
+
+        let amount = 1. - (-FOLLOW_SPEED * delta).exp();
+
+        self.position.x += (target.x - self.position.x) * amount;
+        self.position.y += (target.y - self.position.y) * amount;
+    }
+
+    pub fn pan(&mut self, x: f32, y: f32) {
+        self.position.x += x / self.zoom;
+        self.position.y += y / self.zoom;
+    }
+
+    // positive notches zoom in, negative zoom out
+    pub fn zoom_by(&mut self, notches: f32) {
+
+        if notches == 0. {
+            return;
+        }
+
+        self.zoom = (self.zoom * ZOOM_STEP.powf(notches.signum())).clamp(MIN_ZOOM, MAX_ZOOM);
+    }
+
+    // zooms while keeping the world position under a screen point where it is, like under the mouse
+    pub fn zoom_at(&mut self, notches: f32, screen: &Vec2) {
+
+        let before = self.screen_to_world(screen);
+
+        self.zoom_by(notches);
+
+        let after = self.screen_to_world(screen);
+
+        self.position.x += before.x - after.x;
+        self.position.y += before.y - after.y;
+    }
+
+    // centers on a world area and zooms so all of it fits with a margin of padding world units around it
+    pub fn frame(&mut self, min: &Vec2, max: &Vec2, padding: f32) {
+
+        self.position = Vec2::new((min.x + max.x) / 2., (min.y + max.y) / 2.);
+
+        let width = max.x - min.x + padding * 2.;
+        let height = max.y - min.y + padding * 2.;
+
+        self.zoom = (screen_width() / width).min(screen_height() / height).clamp(MIN_ZOOM, MAX_ZOOM);
+    }
+
+    // the lowest and highest world positions currently on screen
+    pub fn visible_world(&self) -> (Vec2, Vec2) {
+
+        let bottom_left = self.screen_to_world(&Vec2::new(0., screen_height()));
+        let top_right = self.screen_to_world(&Vec2::new(screen_width(), 0.));
+
+        (bottom_left, top_right)
+    }
+
+    pub fn add_shake(&mut self, amount: f32) {
+        self.shake = (self.shake + amount).min(1.);
+    }
+
+    // call once a frame after moving the camera
+    pub fn update(&mut self, delta: f32) {
+
+        self.shake = (self.shake - SHAKE_DECAY * delta).max(0.);
+
+        // squaring makes small shakes subtle and big ones really noticeable
+        let strength = self.shake * self.shake * MAX_SHAKE_OFFSET;
+
+        self.shake_offset = Vec2::new(
+            (rand::random::<f32>() * 2. - 1.) * strength,
+            (rand::random::<f32>() * 2. - 1.) * strength
+        );
+
+        self.clamp_to_bounds();
+    }
+
+    pub fn clamp_to_bounds(&mut self) {
+
+        let (min, max) = match &self.bounds {
+            Some(bounds) => bounds,
+            None => return,
+        };
+
+        // how much of the world is visible either side of the center
+        let half_width = screen_width() / 2. / self.zoom;
+        let half_height = screen_height() / 2. / self.zoom;
+
+        // if the level is smaller than the screen just center it
+        self.position.x = match max.x - min.x <= half_width * 2. {
+            true => (min.x + max.x) / 2.,
+            false => self.position.x.clamp(min.x + half_width, max.x - half_width),
+        };
+
+        self.position.y = match max.y - min.y <= half_height * 2. {
+            true => (min.y + max.y) / 2.,
+            false => self.position.y.clamp(min.y + half_height, max.y - half_height),
+        };
+    }
+
+    // scales everything drawn in screen pixels around the center of the screen. set this before drawing the world
+    pub fn macroquad_camera(&self) -> Camera2D {
+        Camera2D {
+            target: macroquad::math::Vec2::new(screen_width() / 2., screen_height() / 2.),
+            zoom: macroquad::math::Vec2::new(2. * self.zoom / screen_width(), -2. * self.zoom / screen_height()),
+            ..Default::default()
+        }
+    }
+
+    pub fn world_to_screen(&self, world: &Vec2) -> Vec2 {
+        self.world_to_screen_for(world, screen_width(), screen_height())
+    }
+
+    pub fn screen_to_world(&self, screen: &Vec2) -> Vec2 {
+        self.screen_to_world_for(screen, screen_width(), screen_height())
+    }
+
+    fn world_to_screen_for(&self, world: &Vec2, width: f32, height: f32) -> Vec2 {
+
+        let offset = self.offset_for(width, height);
+
+        // where it would be drawn without any zoom
+        let x = world.x - offset.x;
+        let y = height - (world.y - offset.y);
+
+        Vec2::new(
+            width / 2. + (x - width / 2.) * self.zoom,
+            height / 2. + (y - height / 2.) * self.zoom
+        )
+    }
+
+    fn screen_to_world_for(&self, screen: &Vec2, width: f32, height: f32) -> Vec2 {
+
+        let offset = self.offset_for(width, height);
+
+        let x = width / 2. + (screen.x - width / 2.) / self.zoom;
+        let y = height / 2. + (screen.y - height / 2.) / self.zoom;
+
+        Vec2::new(x + offset.x, height - y + offset.y)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const WIDTH: f32 = 1280.;
+    const HEIGHT: f32 = 720.;
+
+    fn assert_close(a: &Vec2, b: &Vec2) {
+        assert!((a.x - b.x).abs() < 0.01 && (a.y - b.y).abs() < 0.01, "({}, {}) isnt ({}, {})", a.x, a.y, b.x, b.y);
+    }
+
+    #[test]
+    fn screen_to_world_and_back_when_zoomed() {
+
+        let mut camera = Camera::new(Vec2::new(300., -150.));
+
+        camera.zoom = 2.5;
+
+        for screen in [Vec2::new(0., 0.), Vec2::new(WIDTH, HEIGHT), Vec2::new(123., 456.)] {
+
+            let world = camera.screen_to_world_for(&screen, WIDTH, HEIGHT);
+
+            assert_close(&camera.world_to_screen_for(&world, WIDTH, HEIGHT), &screen);
+        }
+    }
+
+    #[test]
+    fn zoom_is_around_the_center_of_the_screen() {
+
+        let mut camera = Camera::new(Vec2::new(300., -150.));
+
+        camera.zoom = 0.5;
+
+        let center = camera.screen_to_world_for(&Vec2::new(WIDTH / 2., HEIGHT / 2.), WIDTH, HEIGHT);
+
+        assert_close(&center, &Vec2::new(300., -150.));
+
+        // world y goes up so the top of the screen is further up the world. zoomed out shows twice as much
+        let top_left = camera.screen_to_world_for(&Vec2::new(0., 0.), WIDTH, HEIGHT);
+
+        assert_close(&top_left, &Vec2::new(300. - WIDTH, -150. + HEIGHT));
+    }
+}
@@ -0,0 +1,142 @@
+use macroquad::{color::{Color, WHITE, YELLOW}, input::{get_char_pressed, is_key_down, is_key_pressed, mouse_wheel, KeyCode}, shapes::draw_rectangle, text::draw_text, window::screen_height};
+use serde::{Deserialize, Serialize};
+
+const MAX_MESSAGES: usize = 100;
+const VISIBLE_MESSAGES: usize = 10;
+const LINE_HEIGHT: f32 = 20.;
+
+#[derive(Serialize, Deserialize, Clone)]
+pub struct ChatMessage {
+    // None means the message came from the server (joins, leaves, etc)
+    pub author: Option<String>,
+    pub text: String
+}
+
+impl ChatMessage {
+    pub fn system(text: String) -> Self {
+        Self {
+            author: None,
+            text
+        }
+    }
+}
+
+pub struct Chat {
+    pub messages: Vec<ChatMessage>,
+    pub input: String,
+    pub typing: bool,
+    // how many messages we are scrolled up from the bottom of the log
+    pub scroll: usize
+}
+
+impl Chat {
+
+    pub fn new() -> Self {
+        Self {
+            messages: vec![],
+            input: String::new(),
+            typing: false,
+            scroll: 0
+        }
+    }
+
+    pub fn push(&mut self, message: ChatMessage) {
+        self.messages.push(message);
+
+        if self.messages.len() > MAX_MESSAGES {
+            self.messages.remove(0);
+        }
+    }
+
+    // returns the text the player wants to send when they press enter
+    pub fn update(&mut self) -> Option<String> {
+
+        self.update_scroll();
+
+        if !self.typing {
+
+            if is_key_pressed(KeyCode::Enter) || is_key_pressed(KeyCode::T) {
+                self.typing = true;
+
+                // throw away the key that opened the chat so it doesnt end up in the input box
+                while get_char_pressed().is_some() {}
+            }
+
+            return None;
+        }
+
+        while let Some(character) = get_char_pressed() {
+            if !character.is_control() {
+                self.input.push(character);
+            }
+        }
+
+        if is_key_pressed(KeyCode::Backspace) {
+            self.input.pop();
+        }
+
+        if is_key_pressed(KeyCode::Escape) {
+            self.typing = false;
+            self.input.clear();
+
+            return None;
+        }
+
+        if is_key_pressed(KeyCode::Enter) {
+            self.typing = false;
+            self.scroll = 0;
+
+            let text = std::mem::take(&mut self.input);
+
+            if text.trim().is_empty() {
+                return None;
+            }
+
+            return Some(text);
+        }
+
+        None
+    }
+
+    fn update_scroll(&mut self) {
+
+        let max_scroll = self.messages.len().saturating_sub(VISIBLE_MESSAGES);
+
+        if is_key_pressed(KeyCode::PageUp) || (self.typing && mouse_wheel().1 > 0.) {
+            self.scroll = (self.scroll + 1).min(max_scroll);
+        }
+
+        if is_key_pressed(KeyCode::PageDown) || (self.typing && mouse_wheel().1 < 0.) {
+            self.scroll = self.scroll.saturating_sub(1);
+        }
+
+        // jump back to the newest message
+        if is_key_down(KeyCode::End) {
+            self.scroll = 0;
+        }
+    }
+
+    pub fn draw(&self) {
+
+        let bottom = screen_height() - 40.;
+
+        if self.typing {
+            draw_rectangle(10., bottom - LINE_HEIGHT * VISIBLE_MESSAGES as f32 - 5., 500., LINE_HEIGHT * (VISIBLE_MESSAGES + 1) as f32 + 10., Color::new(0., 0., 0., 0.5));
+
+            draw_text(&format!("> {}_", self.input), 15., bottom + LINE_HEIGHT, 20., WHITE);
+        }
+
+        let end = self.messages.len() - self.scroll.min(self.messages.len());
+        let start = end.saturating_sub(VISIBLE_MESSAGES);
+
+        for (line, message) in self.messages[start..end].iter().rev().enumerate() {
+
+            let y = bottom - LINE_HEIGHT * line as f32;
+
+            match &message.author {
+                Some(author) => draw_text(&format!("{}: {}", author, message.text), 15., y, 20., WHITE),
+                None => draw_text(&message.text, 15., y, 20., YELLOW),
+            };
+        }
+    }
+}
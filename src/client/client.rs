@@ -2,12 +2,12 @@ use std::collections::HashMap;
 
 use gamelibrary::{proxies::macroquad::{color::{self, Color}, math::vec2::Vec2}, time::Time};
 use diff::Diff;
-use liquidators_lib::{game_state::{GameState, GameStateDiff}, physics_square::PhysicsSquare, TickContext};
-use lz4_flex::{compress_prepend_size, decompress_size_prepended};
-use macroquad::{input::{is_key_down, is_key_released, is_mouse_button_released}, texture::Texture2D};
+use gamelibrary::rigid_body::RigidBody;
+use liquidators_lib::{assets::AssetWatcher, camera::Camera, input::{Action, Input, InputState, DEFAULT_BINDINGS_PATH}, chat::{Chat, ChatMessage}, error::NetError, game_state::{GameState, GameStateDiff}, entities::{tick_entities, Entity}, network::{NetworkPacket, ServerConnection, STATE_HISTORY_LENGTH}, physics_square::PhysicsSquare, player_list::{draw_player_list, PlayerInfo}, settings_menu::SettingsMenu, sound::{SoundEvent, SoundMix, SoundPlayer, SPAWN_SOUND}, TickContext};
+use macroquad::{camera::{set_camera, set_default_camera}, color::WHITE, text::draw_text, texture::Texture2D, window::{screen_height, screen_width}};
 use gamelibrary::traits::HasOwner;
-use gamelibrary::traits::HasRigidBody;
 use rand::prelude::SliceRandom;
+use tracing::{debug, debug_span, error, info, trace, warn, Instrument};
 
 use rand::thread_rng;
 
@@ -25,16 +25,30 @@ pub struct Client {
     pub game_state: GameState,
     pub is_host: bool,
     pub last_tick_game_state: GameState,
+    // the most recent states we got from the server, used as baselines for the diffs it sends us
+    pub server_states: Vec<(u64, GameState)>,
     pub textures: HashMap<String, Texture2D>,
     pub sounds: HashMap<String, macroquad::audio::Sound>,
     pub last_tick: Time,
     pub uuid: String,
-    pub server_receive: ewebsock::WsReceiver,
-    pub server_send: ewebsock::WsSender,
-    pub camera_offset: Vec2,
+    pub server: ServerConnection,
+    pub camera: Camera,
     pub update_count: i32,
     pub start_time: Time,
-    pub square_color: Color
+    pub square_color: Color,
+    pub name: String,
+    pub chat: Chat,
+    pub players: Vec<PlayerInfo>,
+    pub spectator: bool,
+    // the uuid of the player the spectator camera is following
+    pub following: Option<String>,
+    pub disconnect_reason: Option<String>,
+    // only set when running with --watch-assets
+    pub asset_watcher: Option<AssetWatcher>,
+    pub sound_player: SoundPlayer,
+    pub sound_events: Vec<SoundEvent>,
+    pub input: Input,
+    pub settings_menu: SettingsMenu
 }
 
 impl Client {
@@ -45,15 +59,44 @@ impl Client {
         
             //macroquad::window::clear_background(macroquad::color::BLACK);
     
-            self.tick();
+            self.input.update();
+
+            debug_span!("tick").in_scope(|| {
+                self.tick();
+            });
             
-            self.game_state.space.step(&self.uuid);
+            debug_span!("physics_step").in_scope(|| {
+                self.game_state.space.step(&self.uuid);
+            });
             
-            self.draw().await;
+            if let Some(asset_watcher) = &mut self.asset_watcher {
+                asset_watcher.reload_changed(&mut self.textures, &mut self.sounds).await;
+            }
+
+            self.draw().instrument(debug_span!("draw")).await;
 
-            self.send_updates();
+            // spectators never own anything so they have nothing to send
+            if !self.spectator {
+                debug_span!("send").in_scope(|| {
+                    self.send_updates();
+                });
+            }
             
-            self.receive_updates();
+            let receive_result = debug_span!("receive").in_scope(|| {
+                self.receive_updates()
+            });
+
+            if let Err(error) = receive_result {
+                error!(%error, "lost connection to the server");
+
+                self.disconnect_reason = Some(error.to_string());
+            }
+
+            if let Some(reason) = &self.disconnect_reason {
+                info!(%reason, "disconnected from server");
+
+                return;
+            }
 
             // we dont want to track the changes that happen to the game state when we receive updates
             // so we set the checkpoint right after we receive the updates
@@ -62,7 +105,7 @@ impl Client {
     
             macroquad::window::next_frame().await;
     
-            if macroquad::input::is_key_down(macroquad::input::KeyCode::J) {
+            if self.input.state.is_down(Action::DumpState) && !self.chat.typing {
                 let state_string = serde_json::to_string_pretty(&self.game_state).unwrap();
     
                 std::fs::write("state.json", state_string).expect("failed to write current state to state.json")
@@ -74,8 +117,7 @@ impl Client {
             //     Duration::from_millis(5)
             // );
     
-            println!("fps: {}",  macroquad::time::get_fps());
-            println!("squares: {}", self.game_state.physics_squares.len());
+            trace!(fps = macroquad::time::get_fps(), entities = self.game_state.entities.len(), "frame");
 
         }
     }
@@ -94,151 +136,219 @@ impl Client {
         //     Err(error) => panic!("failed to serialize game state diff: {}", error),
         // };
 
-        let diff_bytes = bitcode::serialize(&diff).expect("failed to serialize game state diff");
-
-        let compressed_diff_bytes = compress_prepend_size(&diff_bytes);
+        self.send_packet(NetworkPacket::GameStateDiff(diff));
 
-        self.server_send.send(ewebsock::WsMessage::Binary(compressed_diff_bytes.to_vec()));
+    }
 
+    pub fn send_packet(&mut self, packet: NetworkPacket) {
+        self.server.send_packet(&packet);
     }
 
-    pub fn receive_updates(&mut self) {
-        // let mut update_count = 0;
+    pub fn receive_updates(&mut self) -> Result<(), NetError> {
         
         // we loop until there are no new updates
         loop {
 
-            let compressed_game_state_diff_bytes = match self.server_receive.try_recv() {
-                Some(event) => {
-                    match event {
-                        ewebsock::WsEvent::Opened => todo!("unhandled 'Opened' event"),
-                        ewebsock::WsEvent::Message(message) => {
-                            match message {
-                                ewebsock::WsMessage::Binary(bytes) => bytes,
-                                _ => todo!("unhandled message type when trying to receive updates from server")
-                            }
-                        },
-                        ewebsock::WsEvent::Error(error) => todo!("unhandled 'Error' event when trying to receive update from server: {}", error),
-                        ewebsock::WsEvent::Closed => todo!("server closed"),
-                    }
-                },
-                None => break, // this means there are no more updates
+            let packet = match self.server.try_receive()? {
+                Some(packet) => packet,
+                None => return Ok(()), // this means there are no more updates
             };
             
-            let game_state_diff_bytes = decompress_size_prepended(&compressed_game_state_diff_bytes).expect("Failed to decompress incoming update");
+            match packet {
+                NetworkPacket::StateDiff { baseline, sequence, diff } => self.receive_state_diff(baseline, sequence, diff),
+                NetworkPacket::FullState { sequence, game_state } => self.apply_server_state(sequence, game_state),
+                NetworkPacket::ChatMessage(message) => self.chat.push(message),
+                NetworkPacket::PlayerList(players) => self.players = players,
+                NetworkPacket::Sound(sound_event) => {
+                    if sound_event.source != self.uuid {
+                        self.sound_player.play(&sound_event, &self.sounds, &self.camera.position);
+                    }
+                },
+                NetworkPacket::Ping(sent_time) => self.send_packet(NetworkPacket::Pong(sent_time)),
+                NetworkPacket::Disconnect(reason) => {
+                    self.disconnect_reason = Some(reason);
 
-            let game_state_diff: GameStateDiff = match bitcode::deserialize(&game_state_diff_bytes) {
-                Ok(game_state_diff) => game_state_diff,
-                Err(error) => {
-                    panic!("failed to deserialize game state diff: {}", error);
+                    return Ok(());
                 },
-            };
+                NetworkPacket::GameStateDiff(_) | NetworkPacket::Hello { .. } | NetworkPacket::Pong(_) | NetworkPacket::Ack(_) | NetworkPacket::ResyncRequest | NetworkPacket::EditorHello { .. } | NetworkPacket::EditorPresence { .. } => {
+                    warn!("server sent us a packet that only clients should send")
+                },
+                NetworkPacket::FullLevel(_) | NetworkPacket::LevelDiff(_) | NetworkPacket::Collaborators { .. } => {
+                    warn!("server sent us a packet that is only for editors")
+                },
+            }
+
+        }
+
+    }
+
+    pub fn receive_state_diff(&mut self, baseline: u64, sequence: u64, diff: GameStateDiff) {
+
+        let baseline_state = match self.server_states.iter().find(|(server_sequence, _)| *server_sequence == baseline) {
+            Some((_, baseline_state)) => baseline_state,
+            None => {
+                warn!(baseline, "server sent a diff against a state we no longer have, asking for a full state");
+
+                self.send_packet(NetworkPacket::ResyncRequest);
 
-            self.game_state.apply(&game_state_diff);
+                return;
+            },
+        };
+
+        let mut server_state = baseline_state.clone();
 
-            // update_count += 1;
+        server_state.apply(&diff);
+
+        self.apply_server_state(sequence, server_state);
+    }
 
-            //println!("update count: {}", update_count);
+    pub fn apply_server_state(&mut self, sequence: u64, server_state: GameState) {
 
+        let (latest_sequence, latest_server_state) = self.server_states.last().expect("we should always have the initial state");
+
+        // this is older than what we already have so it arrived out of order
+        if sequence <= *latest_sequence {
+            return;
         }
 
-    } 
-    pub async fn draw(&mut self) {
-        for entity in self.game_state.physics_squares.iter_mut() {
+        // only apply what changed on the server since the last state we saw so we dont throw away our own local changes
+        let server_changes = latest_server_state.diff(&server_state);
+
+        let owned_entities = self.owned_entities();
 
-            entity.draw(&self.camera_offset, &self.game_state.space).await;
+        self.game_state.apply(&server_changes);
+
+        self.restore_owned_entities(owned_entities);
+
+        self.server_states.push((sequence, server_state));
+
+        if self.server_states.len() > STATE_HISTORY_LENGTH {
+            self.server_states.remove(0);
         }
+
+        self.send_packet(NetworkPacket::Ack(sequence));
     }
 
-    pub fn connect(url: &str) -> Self {
+    // the server echoes our own changes back to us, but by the time they arrive they are out of date
+    // so we save our entities before applying server changes and put them back afterwards
+    pub fn owned_entities(&mut self) -> Vec<(String, Entity, RigidBody)> {
 
-        let uuid = gamelibrary::uuid();
+        self.game_state.entities.iter()
+            .filter(|(_, entity)| entity.get_owner() == self.uuid)
+            .map(|(id, entity)| {
+                let rigid_body = self.game_state.space.get_rigid_body_mut(entity.rigid_body_handle()).unwrap().clone();
 
-        println!("{}", uuid);
+                (id.clone(), entity.clone(), rigid_body)
+            })
+            .collect()
+    }
 
-        let (server_send, server_receive) = match ewebsock::connect(url, ewebsock::Options::default()) {
-            Ok(result) => result,
-            Err(error) => {
-                panic!("failed to connect to server: {}", error)
-            },
-        }; 
+    pub fn restore_owned_entities(&mut self, owned_entities: Vec<(String, Entity, RigidBody)>) {
 
-        // wait for Opened event from server
-        loop {
-            match server_receive.try_recv() {
-                Some(event) => {
-                    match event {
-                        ewebsock::WsEvent::Opened => {
-                            println!("we got the opened message!");
-                            break;
-                        },
-                        ewebsock::WsEvent::Message(message) => {
-                            match message {
-                                _ => panic!("received a message from the server")
-                            }
-                        },
-                        ewebsock::WsEvent::Error(error) => panic!("received error when trying to connect to server: {}", error),
-                        ewebsock::WsEvent::Closed => panic!("server closed when trying to connect"),
-                        
-                    }
-                },
-                None => continue,
+        for (id, entity, rigid_body) in owned_entities {
+
+            let existing_entity = match self.game_state.entities.get_mut(&id) {
+                Some(existing_entity) => existing_entity,
+                None => continue, // the server removed it so we let it go
+            };
+
+            if let Some(body) = self.game_state.space.get_rigid_body_mut(entity.rigid_body_handle()) {
+                *body = rigid_body;
             }
+
+            *existing_entity = entity;
         }
+    }
 
-        let compressed_game_state_string_bytes = loop {
-
-            match server_receive.try_recv() {
-                Some(event) => {
-                    match event {
-                        ewebsock::WsEvent::Opened => todo!("unhandled opened event on connect"),
-                        ewebsock::WsEvent::Message(message) => {
-                            match message {
-                                ewebsock::WsMessage::Binary(bytes) => break bytes,
-                                _ => todo!("unhandled message type when receiving initial state")
-                            }
-                        },
-                        ewebsock::WsEvent::Error(error) => todo!("unhandled error when receiving initial state: {}", error),
-                        ewebsock::WsEvent::Closed => todo!("unhandled closed event when receiving initial state"),
-                    }
-                },
-                None => continue, // this means that the server would have blocked, so we try again
-            };
-        };
-        
-        let game_state_string_bytes = decompress_size_prepended(&compressed_game_state_string_bytes).expect("Failed to decompress initial state");
-        
-        let game_state_string = match String::from_utf8(game_state_string_bytes.clone()) {
-            Ok(game_state_diff_string) => game_state_diff_string,
-            Err(error) => {
-                panic!("failed to decode game state diff as string {}", error);
-            },
+    pub async fn draw(&mut self) {
+        // zoom only applies to the world, the hud is drawn at normal size on top
+        set_camera(&self.camera.macroquad_camera());
+
+        self.game_state.draw(&self.camera.offset(), &self.textures).await;
+
+        set_default_camera();
+
+        self.chat.draw();
+
+        if self.spectator {
+            self.draw_spectator_hud();
+        }
+
+        if self.input.state.is_down(Action::PlayerList) {
+            draw_player_list(&self.players);
+        }
+
+        self.settings_menu.draw(&self.input);
+    }
+
+    pub fn draw_spectator_hud(&self) {
+
+        let following = self.following.as_ref().and_then(|uuid| self.players.iter().find(|player| player.uuid == *uuid));
+
+        let status = match following {
+            Some(player) => format!("Spectating {}", player.name),
+            None => "Spectating (free camera)".to_string(),
         };
 
-        
+        draw_text(&status, 10., 25., 25., WHITE);
+        draw_text("[ / ] to follow players, arrow keys for free camera", 10., 45., 20., WHITE);
+    }
 
-        let game_state: GameState = match serde_json::from_str(&game_state_string) {
-            Ok(game_state_diff) => game_state_diff,
-            Err(error) => {
-                panic!("failed to deserialize game state diff: {}", error);
-            },
+    pub fn connect(url: &str, name: Option<String>, spectator: bool) -> Result<Self, NetError> {
+
+        let uuid = gamelibrary::uuid();
+
+        info!(%uuid, %url, "connecting to server");
+
+        let mut server = ServerConnection::connect(url)?;
+
+        debug!("websocket opened");
+
+        let started = Time::now();
+
+        // the server sends the full state as soon as we connect. anything else that arrives first can be ignored
+        let (sequence, game_state) = loop {
+            match server.wait_for_packet(&started)? {
+                NetworkPacket::FullState { sequence, game_state } => break (sequence, game_state),
+                // the server is full or we got kicked before we even joined
+                NetworkPacket::Disconnect(reason) => return Err(NetError::Connect(reason)),
+                _ => continue
+            }
         };
+
+        server.send_packet(&NetworkPacket::Ack(sequence));
         
-        Self {
+        // default to a name based on our uuid so players can still tell each other apart
+        let name = name.unwrap_or(format!("Player {}", &uuid[..4]));
+
+        server.send_packet(&NetworkPacket::Hello { uuid: uuid.clone(), name: name.clone(), spectator });
+
+        Ok(Self {
             game_state: game_state.clone(),
             is_host: true,
             last_tick_game_state: game_state.clone(),
+            server_states: vec![(sequence, game_state.clone())],
             textures: HashMap::new(),
             sounds: HashMap::new(),
             last_tick: Time::now(),
             uuid: uuid,
-            server_receive: server_receive,
-            server_send: server_send,
-            camera_offset: Vec2::new(0., 0.),
+            server,
+            camera: Camera::new(Vec2::new(screen_width() / 2., screen_height() / 2.)),
             update_count: 0,
             start_time: Time::now(),
-            square_color: random_color()
-        }
+            square_color: random_color(),
+            name,
+            chat: Chat::new(),
+            players: vec![],
+            spectator,
+            following: None,
+            disconnect_reason: None,
+            asset_watcher: None,
+            sound_player: SoundPlayer::new(SoundMix::default()),
+            sound_events: vec![],
+            input: Input::new(DEFAULT_BINDINGS_PATH),
+            settings_menu: SettingsMenu::new()
+        })
     }
 
 
@@ -246,81 +356,182 @@ impl Client {
 
     }
 
+    // free camera movement for when we arent following anything
     pub fn control_camera(&mut self) {
-        if is_key_down(macroquad::input::KeyCode::Right) {
-            self.camera_offset.x += 1.0 * self.last_tick.elapsed().num_milliseconds() as f32;
+
+        let distance = self.last_tick.elapsed().num_milliseconds() as f32;
+
+        if self.input.state.is_down(Action::CameraRight) {
+            self.camera.pan(distance, 0.);
         }
 
-        if is_key_down(macroquad::input::KeyCode::Left) {
-            self.camera_offset.x -= 1.0 * self.last_tick.elapsed().num_milliseconds() as f32;
+        if self.input.state.is_down(Action::CameraLeft) {
+            self.camera.pan(-distance, 0.);
         }
 
-        if is_key_down(macroquad::input::KeyCode::Down) {
-            self.camera_offset.y -= 1.0 * self.last_tick.elapsed().num_milliseconds() as f32;
+        if self.input.state.is_down(Action::CameraDown) {
+            self.camera.pan(0., -distance);
         }
 
-        if is_key_down(macroquad::input::KeyCode::Up) {
-            self.camera_offset.y += 1.0 * self.last_tick.elapsed().num_milliseconds() as f32;
+        if self.input.state.is_down(Action::CameraUp) {
+            self.camera.pan(0., distance);
         }
+
+        self.camera.zoom_by(self.input.state.mouse_wheel);
     }
 
-    pub fn tick(&mut self) {
+    pub fn follow_player(&mut self) {
+
+        let next_pressed = self.input.state.is_pressed(Action::FollowNext);
+
+        if next_pressed || self.input.state.is_pressed(Action::FollowPrevious) {
+
+            let uuids: Vec<String> = self.players.iter()
+                .filter(|player| !player.spectator)
+                .map(|player| player.uuid.clone())
+                .collect();
+
+            if !uuids.is_empty() {
+
+                let current = self.following.as_ref().and_then(|uuid| uuids.iter().position(|other| other == uuid));
+
+                let next = match (current, next_pressed) {
+                    (None, _) => 0,
+                    (Some(index), true) => (index + 1) % uuids.len(),
+                    (Some(index), false) => (index + uuids.len() - 1) % uuids.len(),
+                };
+
+                self.following = Some(uuids[next].clone());
+            }
+        }
+
+        // moving the camera manually goes back to the free camera
+        let camera_actions = [Action::CameraLeft, Action::CameraRight, Action::CameraUp, Action::CameraDown];
+
+        if camera_actions.iter().any(|action| self.input.state.is_down(*action)) {
+            self.following = None;
+        }
+    }
+
+    // where the camera should be heading, if anywhere. players follow themselves and spectators follow whoever they picked
+    pub fn camera_target(&mut self) -> Option<Vec2> {
+
+        let uuid = match self.spectator {
+            true => self.following.clone()?,
+            false => self.uuid.clone(),
+        };
+
+        // prefer the entity they are controlling but fall back to anything they own
+        let entity = self.game_state.entities.values()
+            .filter(|entity| entity.get_owner() == uuid)
+            .max_by_key(|entity| entity.controllable())?;
+
+        let rigid_body = self.game_state.space.get_rigid_body_mut(entity.rigid_body_handle())?;
+
+        Some(rigid_body.position.clone())
+    }
+
+    pub fn update_camera(&mut self) {
+
+        let delta = self.last_tick.elapsed().num_milliseconds() as f32 / 1000.;
+
+        self.camera.bounds = self.game_state.bounds.camera_bounds();
+
+        if let Some(target) = self.camera_target() {
+            self.camera.follow(&target, delta);
+        }
+
+        self.camera.update(delta);
+    }
+
+    pub fn handle_controls(&mut self) {
 
         self.control_camera();
 
-        if is_key_released(macroquad::input::KeyCode::F5) {
+        // spectators can only look around
+        if self.spectator {
+            self.follow_player();
+
+            return;
+        }
+
+        if self.input.state.is_released(Action::SaveState) {
             let game_state_json = serde_json::to_string_pretty(&self.game_state).unwrap();
 
             std::fs::write("state.json", game_state_json).unwrap();
         }
 
-        if is_key_released(macroquad::input::KeyCode::F6) {
+        if self.input.state.is_released(Action::LoadState) {
             self.game_state = serde_json::from_str(
                 &std::fs::read_to_string("state.json").expect("failed to read state file")
             ).expect("failed to deserialize state file");
         }
 
-        if is_mouse_button_released(macroquad::input::MouseButton::Left) {
-
-            let mouse_pos = macroquad::input::mouse_position();
-
-            self.game_state.physics_squares.push( 
-                PhysicsSquare::new(
-                    &mut self.game_state.space,
-                    Vec2::new(mouse_pos.0 + 20., mouse_pos.1 + 20.),
-                    gamelibrary::rigid_body::RigidBodyType::Dynamic,
-                    20., 
-                    20., 
-                    &self.uuid,
-                    false,
-                    self.square_color
-                )
+        if self.input.state.is_released(Action::SpawnSquare) {
+
+            let position = self.camera.screen_to_world(&self.input.state.mouse_position);
+
+            let physics_square = PhysicsSquare::new(
+                &mut self.game_state.space,
+                position.clone(),
+                gamelibrary::rigid_body::RigidBodyType::Dynamic,
+                20., 
+                20., 
+                &self.uuid,
+                false,
+                self.square_color
             );
-        }
 
-        for index in 0..self.game_state.physics_squares.len() {
+            self.game_state.spawn(Entity::PhysicsSquare(physics_square));
 
-            // take the player out, tick it, then put it back in
-            let mut entity = self.game_state.physics_squares.remove(index);
+            self.sound_events.push(SoundEvent::sfx(SPAWN_SOUND, position, 0.8));
+        }
+    }
 
-            let mut tick_context = TickContext {
-                game_state: &mut self.game_state,
-                is_host: &mut self.is_host,
-                textures: &mut self.textures,
-                sounds: &mut self.sounds,
-                time: &self.last_tick,
-                uuid: &self.uuid,
-                camera_offset: &mut self.camera_offset,
-            };
+    pub fn tick(&mut self) {
 
-            // we only tick the entity if we own it
-            if entity.get_owner() == *self.uuid {
-                entity.tick(&mut tick_context);
+        self.settings_menu.update(&mut self.input);
+
+        if !self.settings_menu.open {
+            if let Some(text) = self.chat.update() {
+                self.send_packet(
+                    NetworkPacket::ChatMessage(ChatMessage { author: Some(self.name.clone()), text })
+                );
             }
-            
-            // put the entity back in the same index so it doesnt FUCK things up
-            self.game_state.physics_squares.insert(index, entity)
+        }
+
+        // dont let typing in the chat or picking controls move the camera or spawn things
+        let menus_open = self.chat.typing || self.settings_menu.open;
+
+        if !menus_open {
+            self.handle_controls();
+        }
+
+        let no_input = InputState::default();
+
+        let mut tick_context = TickContext {
+            game_state: &mut self.game_state,
+            is_host: &mut self.is_host,
+            textures: &mut self.textures,
+            sounds: &mut self.sounds,
+            sound_events: &mut self.sound_events,
+            time: &self.last_tick,
+            uuid: &self.uuid,
+            camera: &mut self.camera,
+            input: match menus_open {
+                true => &no_input,
+                false => &self.input.state,
+            },
+        };
+
+        tick_entities(&mut tick_context);
+
+        self.update_camera();
+
+        for sound_event in std::mem::take(&mut self.sound_events) {
+            self.sound_player.play(&sound_event, &self.sounds, &self.camera.position);
 
+            self.send_packet(NetworkPacket::Sound(sound_event));
         }
 
         self.last_tick = Time::now(); 
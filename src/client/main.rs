@@ -1,7 +1,8 @@
 
-use gamelibrary::{proxies::macroquad::math::vec2::Vec2, rigid_body::RigidBodyType};
-use liquidators_lib::physics_square::PhysicsSquare;
-use macroquad::{miniquad::conf::Platform, window::Conf};
+use gamelibrary::proxies::macroquad::math::vec2::Vec2;
+use liquidators_lib::{args::{arg_value, parse_arg}, assets::{AssetManager, AssetWatcher}, entities::Entity, input::{Input, InputScript}, logging, player::Player, sound::SoundMix};
+use macroquad::{color::{BLACK, WHITE}, input::{is_key_pressed, KeyCode}, miniquad::conf::Platform, text::draw_text, window::{clear_background, next_frame, Conf}};
+use tracing::error;
 use client::Client;
 
 pub mod client;
@@ -19,28 +20,102 @@ fn window_conf() -> Conf {
     conf
 }
 
+// keep the window open so the player can actually read why they cant play
+async fn show_error(message: String) {
+    loop {
+        clear_background(BLACK);
+
+        draw_text(&message, 20., 40., 30., WHITE);
+        draw_text("press escape to quit", 20., 80., 20., WHITE);
+
+        if is_key_pressed(KeyCode::Escape) {
+            return;
+        }
+
+        next_frame().await;
+    }
+}
+
 #[macroquad::main(window_conf)]
 async fn main() {
 
-    let mut client = Client::connect("ws://voxany.net:5556");
+    let args: Vec<String> = std::env::args().collect();
+
+    // keep this alive until the client exits or we lose buffered file logs
+    let _log_guard = logging::init("client", arg_value(&args, "--log-filter"), arg_value(&args, "--log-dir"));
+
+    // the player can pick a name with --name <name>
+    let name = arg_value(&args, "--name");
+
+    // spectators get the full game state but never own anything
+    let spectator = args.contains(&"--spectate".to_string());
+
+    let mut client = match Client::connect("ws://voxany.net:5556", name, spectator) {
+        Ok(client) => client,
+        Err(error) => {
+            error!(%error, "failed to connect to server");
+
+            show_error(format!("Failed to connect to server: {}", error)).await;
+
+            return;
+        },
+    };
 
-    // client.game_state.entities.push(
-    //     game::entities::Entity::Player(Player::new(client.uuid.clone()))
-    // );
+    // everything is loaded up front so nothing has to wait on a file once the game is running
+    let mut assets = AssetManager::discover();
 
-    let physics_square = PhysicsSquare::new(
-        &mut client.game_state.space,
-        Vec2::new(50., 500.),
-        RigidBodyType::Dynamic,
-        20., 
-        20., 
-        &client.uuid,
-        true,
-        client.square_color
-    );
+    assets.load_with_loading_screen(&mut client.textures, &mut client.sounds).await;
 
-    client.game_state.physics_squares.push(physics_square);
+    for (key, reason) in &assets.failed {
+        error!(%key, %reason, "missing asset");
+    }
+
+    // controls are rebound from the settings menu and saved here
+    if let Some(bindings_path) = arg_value(&args, "--bindings") {
+        client.input = Input::new(&bindings_path);
+    }
+
+    // replay input from a file instead of the keyboard, for testing without anyone at the controls
+    if let Some(script_path) = arg_value(&args, "--input-script") {
+        match InputScript::load(&script_path) {
+            Ok(script) => client.input.script = Some(script),
+            Err(error) => error!(path = %script_path, %error, "failed to load input script"),
+        }
+    }
+
+    // volumes go from 0 to 1, like --sfx-volume 0.5
+    let volume = |flag: &str, default: f32| parse_arg::<f32>(&args, flag).unwrap_or(default).clamp(0., 1.);
+
+    let default_mix = SoundMix::default();
+
+    client.sound_player.mix = SoundMix {
+        master: volume("--master-volume", default_mix.master),
+        music: volume("--music-volume", default_mix.music),
+        sfx: volume("--sfx-volume", default_mix.sfx),
+    };
+
+    // artists can run with --watch-assets to see their changes without restarting
+    if args.contains(&"--watch-assets".to_string()) {
+        client.asset_watcher = match AssetWatcher::start() {
+            Ok(asset_watcher) => Some(asset_watcher),
+            Err(error) => {
+                error!(%error, "failed to watch assets");
+
+                None
+            },
+        };
+    }
+
+    if !spectator {
+        let player = Player::new(&mut client.game_state.space, Vec2::new(50., 500.), &client.uuid);
+
+        client.game_state.spawn(Entity::Player(player));
+    }
 
     client.run().await;
 
+    if let Some(reason) = client.disconnect_reason {
+        show_error(format!("Disconnected: {}", reason)).await;
+    }
+
 }
\ No newline at end of file
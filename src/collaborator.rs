@@ -0,0 +1,27 @@
+use gamelibrary::proxies::macroquad::math::vec2::Vec2;
+use macroquad::color::{Color, BLUE, GREEN, MAGENTA, ORANGE, PINK, PURPLE, SKYBLUE, YELLOW};
+use serde::{Deserialize, Serialize};
+
+// someone editing the shared level, as the server tells the other editors about them
+#[derive(Serialize, Deserialize, Clone, PartialEq)]
+pub struct Collaborator {
+    pub uuid: String,
+    pub name: String,
+    // where their mouse is in the level, in world coordinates
+    pub cursor: Vec2,
+    // the structures they have selected, which are also the ones they hold the lock on
+    pub selected: Vec<String>
+}
+
+impl Collaborator {
+
+    // picked from the uuid so everyone sees the same person in the same color
+    pub fn color(&self) -> Color {
+
+        let colors = [BLUE, GREEN, MAGENTA, ORANGE, PINK, PURPLE, SKYBLUE, YELLOW];
+
+        let index = self.uuid.bytes().map(|byte| byte as usize).sum::<usize>() % colors.len();
+
+        colors[index]
+    }
+}
@@ -0,0 +1,65 @@
+use gamelibrary::proxies::macroquad::math::vec2::Vec2;
+use liquidators_lib::{input::Action, world_bounds::EdgeBehavior};
+use macroquad::{color::{Color, GREEN, RED, SKYBLUE}, shapes::{draw_circle_lines, draw_line}, window::screen_height};
+
+use crate::editor::Editor;
+
+// world units left between the structures and the edges when fitting the bounds to the level
+const BOUNDS_PADDING: f32 = 200.;
+const SPAWN_POINT_RADIUS: f32 = 8.;
+
+impl Editor {
+
+    pub fn update_bounds(&mut self) {
+
+        if !self.input.state.is_pressed(Action::FitBounds) {
+            return;
+        }
+
+        match self.structures_area(self.structure_ids()) {
+            Some((min, max)) => {
+                self.level.bounds = self.level.bounds.around(&min, &max, BOUNDS_PADDING);
+
+                self.set_status("fit the world bounds to the level".to_string());
+            },
+            None => self.set_status("theres nothing to fit the world bounds to".to_string()),
+        }
+    }
+
+    // open edges arent drawn since nothing happens there
+    pub fn draw_bounds(&self) {
+
+        let bounds = &self.level.bounds;
+
+        let offset = self.camera.offset();
+        let thickness = 2. / self.camera.zoom;
+
+        let screen = |point: Vec2| (point.x - offset.x, screen_height() - (point.y - offset.y));
+
+        let (left, bottom) = screen(bounds.min.clone());
+        let (right, top) = screen(bounds.max.clone());
+
+        let edges = [
+            (bounds.left, (left, bottom), (left, top)),
+            (bounds.right, (right, bottom), (right, top)),
+            (bounds.bottom, (left, bottom), (right, bottom)),
+            (bounds.top, (left, top), (right, top)),
+        ];
+
+        for (behavior, (start_x, start_y), (end_x, end_y)) in edges {
+
+            let color = match behavior {
+                EdgeBehavior::Open => continue,
+                EdgeBehavior::Wall => Color::new(1., 1., 1., 0.5),
+                EdgeBehavior::Kill => RED,
+                EdgeBehavior::Wrap => SKYBLUE,
+            };
+
+            draw_line(start_x, start_y, end_x, end_y, thickness, color);
+        }
+
+        let (spawn_x, spawn_y) = screen(bounds.spawn_point.clone());
+
+        draw_circle_lines(spawn_x, spawn_y, SPAWN_POINT_RADIUS / self.camera.zoom, thickness, GREEN);
+    }
+}
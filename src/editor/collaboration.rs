@@ -0,0 +1,351 @@
+use std::collections::HashMap;
+
+use diff::Diff;
+use gamelibrary::{proxies::macroquad::math::vec2::Vec2, time::Time};
+use liquidators_lib::{collaborator::Collaborator, error::NetError, level::{Level, LevelDiff}, network::{NetworkPacket, ServerConnection}};
+use macroquad::{shapes::{draw_line, draw_triangle}, text::draw_text, window::screen_height};
+use tracing::{debug, error, info};
+
+use crate::{editor::Editor, history::History};
+
+// cursors and selections are sent at most this often so moving the mouse around doesnt flood the server
+const PRESENCE_INTERVAL_MILLISECONDS: i64 = 50;
+// how big other editors' cursors are, in pixels
+const CURSOR_SIZE: f32 = 14.;
+
+// a connection to a server that shares one level between everyone editing it.
+// changes go both ways as level diffs, the same way game state changes do
+pub struct Collaboration {
+    pub uuid: String,
+    pub name: String,
+    pub server: ServerConnection,
+    // the shared level as of the last changes we sent or received, without selections or drags.
+    // our own edits are found by diffing the level against this, like the history does
+    pub synced_level: Level,
+    // everyone editing the level, including us
+    pub collaborators: Vec<Collaborator>,
+    // structure id -> uuid of the editor that has it selected
+    pub locks: HashMap<String, String>,
+    // the cursor and selection we last told the server about
+    pub sent_presence: Option<(Vec2, Vec<String>)>,
+    pub last_presence: Time,
+    pub disconnect_reason: Option<String>
+}
+
+// whether applying the diff would change any of the given structures or their rigid bodies
+pub fn diff_touches(level: &Level, diff: &LevelDiff, ids: &[String]) -> bool {
+
+    if ids.is_empty() {
+        return false;
+    }
+
+    let mut before = level.without_editor_state();
+
+    let mut after = before.clone();
+
+    after.apply(diff);
+
+    ids.iter().any(|id| {
+
+        let structure = match before.structures.get(id) {
+            Some(structure) => structure.clone(),
+            None => return false,
+        };
+
+        if after.structures.get(id) != Some(&structure) {
+            return true;
+        }
+
+        before.space.get_rigid_body_mut(&structure.rigid_body_handle).cloned() != after.space.get_rigid_body_mut(&structure.rigid_body_handle).cloned()
+    })
+}
+
+impl Collaboration {
+
+    // returns the shared level along with the connection so the editor can switch over to it
+    pub fn connect(url: &str, name: Option<String>) -> Result<(Self, Level), NetError> {
+
+        let uuid = gamelibrary::uuid();
+
+        info!(%uuid, %url, "connecting to server to edit together");
+
+        let mut server = ServerConnection::connect(url)?;
+
+        debug!("websocket opened");
+
+        let started = Time::now();
+
+        let name = name.unwrap_or(format!("Editor {}", &uuid[..4]));
+
+        server.send_packet(&NetworkPacket::EditorHello { uuid: uuid.clone(), name: name.clone() });
+
+        // the server sends everyone a full game state when they connect, before it knows we are an editor. we only want the level
+        let level = loop {
+            match server.wait_for_packet(&started)? {
+                NetworkPacket::FullLevel(level) => break level,
+                NetworkPacket::Disconnect(reason) => return Err(NetError::Connect(reason)),
+                _ => continue
+            }
+        };
+
+        let collaboration = Self {
+            uuid,
+            name,
+            server,
+            synced_level: level.clone(),
+            collaborators: vec![],
+            locks: HashMap::new(),
+            sent_presence: None,
+            last_presence: Time::now(),
+            disconnect_reason: None,
+        };
+
+        Ok((collaboration, level))
+    }
+
+    pub fn send_packet(&mut self, packet: NetworkPacket) {
+        self.server.send_packet(&packet);
+    }
+
+    // everything the server sent since last frame
+    pub fn receive_packets(&mut self) -> Result<Vec<NetworkPacket>, NetError> {
+
+        let mut packets = vec![];
+
+        while let Some(packet) = self.server.try_receive()? {
+            packets.push(packet);
+        }
+
+        Ok(packets)
+    }
+}
+
+impl Editor {
+
+    pub fn join_collaboration(&mut self, collaboration: Collaboration, level: Level) {
+
+        info!(name = %collaboration.name, structures = level.structures.len(), "joined the shared level");
+
+        self.set_status(format!("editing together as {}", collaboration.name));
+
+        // the shared level doesnt belong to a file until someone saves it
+        self.level_path = None;
+        self.saved_level = level.clone();
+        self.history = History::new(&level);
+        self.level = level;
+        self.collaboration = Some(collaboration);
+    }
+
+    // structures someone else has selected, which we cant select or change until they let go of them
+    pub fn locked_by_others(&self) -> Vec<String> {
+        match &self.collaboration {
+            Some(collaboration) => collaboration.locks.iter()
+                .filter(|(_, holder)| **holder != collaboration.uuid)
+                .map(|(id, _)| id.clone())
+                .collect(),
+            None => vec![],
+        }
+    }
+
+    // the name of whoever holds the lock on a structure
+    pub fn lock_holder(&self, id: &String) -> Option<String> {
+
+        let collaboration = self.collaboration.as_ref()?;
+
+        let holder = collaboration.locks.get(id)?;
+
+        collaboration.collaborators.iter().find(|collaborator| collaborator.uuid == *holder).map(|collaborator| collaborator.name.clone())
+    }
+
+    // call once a frame after editing with the level without editor state.
+    // sends our changes and where our mouse is, then applies everyone else's changes
+    pub fn update_collaboration(&mut self, snapshot: &Level) {
+
+        let presence = (self.mouse_world(), self.selected_ids());
+
+        let collaboration = match &mut self.collaboration {
+            Some(collaboration) if collaboration.disconnect_reason.is_none() => collaboration,
+            _ => return,
+        };
+
+        if *snapshot != collaboration.synced_level {
+            let diff = collaboration.synced_level.diff(snapshot);
+
+            collaboration.send_packet(NetworkPacket::LevelDiff(diff));
+
+            collaboration.synced_level = snapshot.clone();
+        }
+
+        if collaboration.sent_presence.as_ref() != Some(&presence) && collaboration.last_presence.elapsed().num_milliseconds() >= PRESENCE_INTERVAL_MILLISECONDS {
+
+            collaboration.send_packet(NetworkPacket::EditorPresence { cursor: presence.0.clone(), selected: presence.1.clone() });
+
+            collaboration.sent_presence = Some(presence);
+            collaboration.last_presence = Time::now();
+        }
+
+        let packets = match collaboration.receive_packets() {
+            Ok(packets) => packets,
+            Err(error) => {
+                error!(%error, "lost connection to the server");
+
+                collaboration.disconnect_reason = Some(error.to_string());
+
+                self.set_status(format!("lost connection to the server: {}", error));
+
+                return;
+            },
+        };
+
+        for packet in packets {
+            self.receive_collaboration_packet(packet);
+        }
+    }
+
+    pub fn receive_collaboration_packet(&mut self, packet: NetworkPacket) {
+
+        let collaboration = match &mut self.collaboration {
+            Some(collaboration) => collaboration,
+            None => return,
+        };
+
+        match packet {
+            NetworkPacket::LevelDiff(diff) => {
+
+                self.level.apply(&diff);
+
+                collaboration.synced_level.apply(&diff);
+
+                // other people's changes arent ours to undo
+                self.history.baseline.apply(&diff);
+            },
+            NetworkPacket::FullLevel(level) => {
+
+                collaboration.synced_level = level.clone();
+
+                self.history = History::new(&level);
+                self.level = level;
+            },
+            NetworkPacket::Collaborators { collaborators, locks } => {
+
+                collaboration.collaborators = collaborators;
+                collaboration.locks = locks;
+
+                self.drop_locked_selection();
+            },
+            NetworkPacket::Ping(sent_time) => collaboration.send_packet(NetworkPacket::Pong(sent_time)),
+            NetworkPacket::Disconnect(reason) => {
+                info!(%reason, "disconnected from server");
+
+                collaboration.disconnect_reason = Some(reason.clone());
+
+                self.set_status(format!("disconnected from the server: {}", reason));
+            },
+            _ => debug!("server sent the editor a packet it doesnt use"),
+        }
+    }
+
+    // when two people select the same structure at once the server gives it to whoever it heard from first,
+    // so the other one has to let go of it, even in the middle of dragging it
+    pub fn drop_locked_selection(&mut self) {
+
+        let locked = self.locked_by_others();
+
+        let lost: Vec<String> = self.selected_ids().into_iter().filter(|id| locked.contains(id)).collect();
+
+        if lost.is_empty() {
+            return;
+        }
+
+        let drag_lost = self.selection.drag.as_ref().is_some_and(|drag| lost.iter().any(|id| drag.start_positions.contains_key(id)));
+
+        if drag_lost {
+            if let Some(drag) = self.selection.drag.take() {
+                for id in drag.start_positions.keys() {
+                    if let Some(structure) = self.level.structures.get_mut(id) {
+                        structure.dragging = false;
+                    }
+                }
+            }
+
+            self.snapping.guides.clear();
+        }
+
+        for id in &lost {
+            if let Some(structure) = self.level.structures.get_mut(id) {
+                structure.clear_editor_state();
+            }
+        }
+
+        let holder = self.lock_holder(&lost[0]).unwrap_or("someone".to_string());
+
+        self.set_status(format!("{} got to that first", holder));
+    }
+
+    // the other editors' selections and cursors, drawn with the level's camera
+    pub fn draw_collaborators(&mut self) {
+
+        let collaboration = match &self.collaboration {
+            Some(collaboration) => collaboration,
+            None => return,
+        };
+
+        let offset = self.camera.offset();
+
+        let thickness = 2. / self.camera.zoom;
+
+        for collaborator in collaboration.collaborators.iter().filter(|collaborator| collaborator.uuid != collaboration.uuid) {
+
+            let color = collaborator.color();
+
+            for id in &collaborator.selected {
+
+                let structure = match self.level.structures.get(id) {
+                    Some(structure) => structure,
+                    None => continue,
+                };
+
+                let corners = structure.corners(&mut self.level.space);
+
+                for index in 0..corners.len() {
+                    let (start, end) = (&corners[index], &corners[(index + 1) % corners.len()]);
+
+                    draw_line(
+                        start.x - offset.x,
+                        screen_height() - (start.y - offset.y),
+                        end.x - offset.x,
+                        screen_height() - (end.y - offset.y),
+                        thickness,
+                        color
+                    );
+                }
+            }
+
+            let (x, y) = (collaborator.cursor.x - offset.x, screen_height() - (collaborator.cursor.y - offset.y));
+
+            let size = CURSOR_SIZE / self.camera.zoom;
+
+            draw_triangle(
+                macroquad::math::Vec2::new(x, y),
+                macroquad::math::Vec2::new(x, y + size),
+                macroquad::math::Vec2::new(x + size * 0.7, y + size * 0.7),
+                color
+            );
+
+            draw_text(&collaborator.name, x + size, y + size * 1.5, 18. / self.camera.zoom, color);
+        }
+    }
+
+    pub fn collaboration_status(&self) -> Option<String> {
+
+        let collaboration = self.collaboration.as_ref()?;
+
+        if let Some(reason) = &collaboration.disconnect_reason {
+            return Some(format!("offline: {}", reason));
+        }
+
+        let others = collaboration.collaborators.iter().filter(|collaborator| collaborator.uuid != collaboration.uuid).count();
+
+        Some(format!("editing as {} with {} others", collaboration.name, others))
+    }
+}
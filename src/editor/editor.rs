@@ -1,85 +1,151 @@
-use gamelibrary::{collider::Collider, proxies::macroquad::{color::colors::RED, math::vec2::Vec2}, rigid_body::RigidBody, translate_coordinates};
-use liquidators_lib::{level::Level, structure::Structure};
-use macroquad::{input::{self, is_key_down, is_key_pressed, is_mouse_button_released, mouse_position}, window::screen_height};
+use std::collections::HashMap;
+
+use gamelibrary::{collider::Collider, proxies::macroquad::{color::colors::RED, math::vec2::Vec2}, rigid_body::{RigidBody, RigidBodyType}, space::RigidBodyHandle, time::Time};
+use liquidators_lib::{assets::AssetWatcher, camera::Camera, input::{Action, Input}, level::Level, structure::{apply_friction, Shape, Structure}};
+use macroquad::{audio::Sound, camera::{set_camera, set_default_camera}, color::WHITE, input::{is_quit_requested, prevent_quit}, shapes::draw_rectangle, text::draw_text, texture::Texture2D, time::get_frame_time, window::{screen_height, screen_width}};
 use gamelibrary::traits::HasRigidBody;
+use tracing::{debug_span, Instrument};
+
+use crate::{collaboration::Collaboration, file_dialog::FileDialog, history::History, inspector::Inspector, numeric_entry::NumericEntry, play_test::PlayTest, saving::DiscardPrompt, selection::Selection, snapping::Snapping, toolbox::{Tool, Toolbox}};
+
+// how long status messages stay on screen
+const STATUS_SECONDS: i64 = 3;
 
 pub struct Editor {
-    pub level: Level
+    pub level: Level,
+    pub textures: HashMap<String, Texture2D>,
+    pub sounds: HashMap<String, Sound>,
+    pub asset_watcher: Option<AssetWatcher>,
+    pub input: Input,
+    // where the level was last saved or opened from
+    pub level_path: Option<String>,
+    // the level as it was last saved, used to tell if there are unsaved changes
+    pub saved_level: Level,
+    pub dirty: bool,
+    pub history: History,
+    pub toolbox: Toolbox,
+    pub selection: Selection,
+    pub snapping: Snapping,
+    pub numeric_entry: Option<NumericEntry>,
+    pub inspector: Inspector,
+    pub camera: Camera,
+    // where the mouse was last frame while panning the camera, in screen coordinates
+    pub pan_last: Option<Vec2>,
+    // the level running with a player in it, while play testing
+    pub play_test: Option<PlayTest>,
+    // only set when editing together with --connect
+    pub collaboration: Option<Collaboration>,
+    pub file_dialog: Option<FileDialog>,
+    pub discard_prompt: Option<DiscardPrompt>,
+    pub last_autosave: Time,
+    // a message shown at the bottom of the screen for a few seconds, like "saved levels/test.json"
+    pub status: Option<(String, Time)>,
+    pub quit: bool
 }                                                                                                                                                                                                                                                                                                                                                                                                                                                                                                                                                                                                                                                                                                                                 
 
 impl Editor {
 
-    pub fn spawn_structure(&mut self) {
-        if is_key_pressed(input::KeyCode::E) {
-
-            let rigid_body_handle = self.level.space.insert_rigid_body(
-                RigidBody { 
-                    position: Vec2::new(mouse_position().0 - 20., (mouse_position().1 * -1. + screen_height()) - 20.), 
-                    rotation: 0., 
-                    angular_velocity: 0.,
-                    velocity: Vec2::ZERO, 
-                    body_type: gamelibrary::rigid_body::RigidBodyType::Dynamic, 
-                    owner: "host".to_string(), 
-                    collider: Collider { 
-                        hx: 20., 
-                        hy: 20., 
-                        restitution: 0., 
-                        mass: 10., 
-                        owner: "host".to_string() 
-                    }
+    // the structure is spawned with the given half extents, centered on position
+    pub fn insert_structure(&mut self, position: Vec2, hx: f32, hy: f32, body_type: RigidBodyType, shape: Shape) {
+
+        let rigid_body_handle = self.level.space.insert_rigid_body(
+            RigidBody { 
+                position, 
+                rotation: 0., 
+                angular_velocity: 0.,
+                velocity: Vec2::ZERO, 
+                body_type, 
+                owner: "host".to_string(), 
+                collider: Collider { 
+                    hx, 
+                    hy, 
+                    restitution: 0., 
+                    mass: 10., 
+                    owner: "host".to_string() 
                 }
-            );
-
-            let new_structure = Structure { 
-                rigid_body_handle: rigid_body_handle,
-                color: RED,
-                menu: None,
-                selected: false,
-                dragging: false,
-                drag_offset: None
-            };
-            
-            self.level.structures.push(new_structure);
-
-        }
-
-        if is_key_pressed(input::KeyCode::Q) {
-
-            let rigid_body_handle = self.level.space.insert_rigid_body(
-                RigidBody { 
-                    position: Vec2::new(mouse_position().0 - 20., (mouse_position().1 * -1. + screen_height()) - 20.), 
-                    rotation: 0., 
-                    angular_velocity: 0.,
-                    velocity: Vec2::ZERO, 
-                    body_type: gamelibrary::rigid_body::RigidBodyType::Fixed, 
-                    owner: "host".to_string(), 
-                    collider: Collider { 
-                        hx: 20., 
-                        hy: 20., 
-                        restitution: 0., 
-                        mass: 10., 
-                        owner: "host".to_string() 
-                    }
-                }
-            );
-
-            let new_structure = Structure { 
-                rigid_body_handle: rigid_body_handle,
-                color: RED,
-                menu: None,
-                selected: false,
-                dragging: false,
-                drag_offset: None
-            };
-            
-            self.level.structures.push(new_structure);
+            }
+        );
+
+        let new_structure = Structure { 
+            owner: "host".to_string(),
+            rigid_body_handle: rigid_body_handle,
+            color: RED,
+            menu: None,
+            selected: false,
+            dragging: false,
+            drag_offset: None,
+            resize_anchor: None,
+            texture: None,
+            tags: vec![],
+            shape,
+            friction: 0.,
+            kinematic: false
+        };
+        
+        self.level.structures.insert(gamelibrary::uuid(), new_structure);
+    }
+
+    // structures are only handles into the space so the rigid body has to go too, otherwise it stays behind as an invisible collider
+    pub fn remove_structure(&mut self, id: &String) {
+        if let Some(structure) = self.level.structures.remove(id) {
+            self.level.space.remove_rigid_body(&structure.rigid_body_handle);
+        }
+    }
+
+    pub fn spawn_structure(&mut self) {
 
+        let mouse_position = self.mouse_world();
+
+        let position = self.snapping.snap_point(&Vec2::new(mouse_position.x - 20., mouse_position.y - 20.));
+
+        if self.input.state.is_pressed(Action::SpawnDynamicStructure) {
+            self.insert_structure(position.clone(), 20., 20., RigidBodyType::Dynamic, Shape::Rectangle);
+        }
+
+        if self.input.state.is_pressed(Action::SpawnFixedStructure) {
+            self.insert_structure(position, 20., 20., RigidBodyType::Fixed, Shape::Rectangle);
         }
     }
 
     pub fn step_space(&mut self) {
-        if is_key_down(input::KeyCode::F) {
-            self.level.space.step(&"host".to_string());
+
+        if !self.input.state.is_down(Action::StepPhysics) {
+            return;
+        }
+
+        // structures other editors are holding stay where they are
+        let mut locked: Vec<(RigidBodyHandle, RigidBody)> = vec![];
+
+        for id in self.locked_by_others() {
+            if let Some(structure) = self.level.structures.get(&id) {
+
+                let handle = structure.rigid_body_handle.clone();
+
+                if let Some(rigid_body) = self.level.space.get_rigid_body_mut(&handle) {
+                    locked.push((handle, rigid_body.clone()));
+                }
+            }
+        }
+
+        self.level.space.step(&"host".to_string());
+
+        // the space doesnt know about kinematic structures or friction
+        let delta = get_frame_time();
+
+        let handles: Vec<RigidBodyHandle> = self.level.structures.values().map(|structure| structure.rigid_body_handle.clone()).collect();
+
+        for structure in self.level.structures.values() {
+            structure.move_kinematic(&mut self.level.space, delta);
+        }
+
+        for handle in handles {
+            apply_friction(&mut self.level.space, &handle, self.level.structures.values(), delta);
+        }
+
+        for (handle, rigid_body) in locked {
+            if let Some(stepped) = self.level.space.get_rigid_body_mut(&handle) {
+                *stepped = rigid_body;
+            }
         }
     }
 
@@ -90,74 +156,241 @@ impl Editor {
         
         self.step_space();
 
+        self.update_snapping();
+
+        self.update_bounds();
+
+        // dragging the camera around takes the mouse away from everything else
+        if self.update_view() {
+            self.handle_menus();
+
+            return;
+        }
+
+        // the inspector panel gets clicks before anything behind it
+        if self.mouse_over_inspector() {
+            self.update_inspector();
+
+            self.handle_menus();
+
+            return;
+        }
+
+        self.update_toolbox();
+
+        // the shape tools use the mouse for placing, so structures only react to it with the select tool
+        if self.toolbox.tool != Tool::Select {
+            self.handle_menus();
+
+            return;
+        }
+
+        // checked before the menus handle this frame's click, which closes them
+        let menu_open = self.level.structures.values().any(|structure| structure.menu.is_some());
+
+        // someone else is editing these so they dont get menus or resize handles here
+        let locked = self.locked_by_others();
+
         // tick all Structures
-        for structure_index in 0..self.level.structures.len() {
-            let mut structure = self.level.structures.remove(structure_index);
+        for id in self.structure_ids().into_iter().filter(|id| !locked.contains(id)) {
+            let mut structure = self.level.structures.remove(&id).unwrap();
 
-            structure.tick_editor(&mut self.level);
+            structure.tick_editor(&mut self.level, &self.input.state, &self.camera, self.snapping.grid_size());
 
-            self.level.structures.insert(structure_index, structure);
+            self.level.structures.insert(id, structure);
 
 
         }
 
         self.handle_menus();
 
+        self.update_selection(menu_open);
+
+    }
+
+    // sorted so structures always tick and draw in the same order
+    pub fn structure_ids(&self) -> Vec<String> {
+        let mut ids: Vec<String> = self.level.structures.keys().cloned().collect();
+
+        ids.sort();
+
+        ids
     }
 
     pub fn handle_menus(&mut self) {
         // this needs to be a function on the editor struct because structures cannot delete themselves
 
-        let mut structure_index = 0;
-        let mut structures_length = self.level.structures.len();
+        for id in self.structure_ids() {
 
-        loop {
+            let structure = self.level.structures.remove(&id).unwrap();
+
+            let result = structure.handle_menu(&mut self.level.space);
 
-            if structure_index >= structures_length {
-                break;
+            // None means the structure was deleted
+            if let Some(structure) = result {
+                self.level.structures.insert(id, structure);
             }
+        }
+            
+    }
 
-            let structure = self.level.structures.remove(structure_index);
+    pub fn draw_hud(&self) {
 
-            let result = structure.handle_menu(&mut self.level.space);
+        let unsaved = match self.dirty {
+            true => " *",
+            false => "",
+        };
 
-            match result {
-                Some(structure) => {
-                    self.level.structures.insert(structure_index, structure);
+        draw_text(&format!("{}{}", self.level_name(), unsaved), 10., 25., 25., WHITE);
 
-                    structure_index += 1;
-                },
-                None => {
-                    structures_length -= 1;
+        draw_text(&self.snapping_status(), 10., 50., 20., WHITE);
 
-                    // we dont increment the index
-                },
-            };
-            
+        if let Some(collaboration_status) = self.collaboration_status() {
+            draw_text(&collaboration_status, 10., 75., 20., WHITE);
+        }
+
+        if let Some((status, shown_at)) = &self.status {
+            if shown_at.elapsed().num_seconds() < STATUS_SECONDS {
+                draw_text(status, 10., screen_height() - 15., 20., WHITE);
+            }
+        }
+
+        self.draw_numeric_entry();
+
+        if let Some(file_dialog) = &self.file_dialog {
+            file_dialog.draw();
+        }
+
+        self.draw_discard_prompt();
+    }
+
+    pub fn draw_discard_prompt(&self) {
+        if let Some(discard_prompt) = &self.discard_prompt {
+            draw_rectangle(0., 0., screen_width(), screen_height(), macroquad::color::Color::new(0., 0., 0., 0.8));
+
+            draw_text(&discard_prompt.message(), 40., screen_height() / 2., 30., WHITE);
         }
-    
-            
     }
 
     pub async fn draw(&mut self) {
 
-        for structure in &mut self.level.structures {
-            structure.draw(&Vec2::new(0., 0.), &self.level.space).await;
+        if self.play_test.is_some() {
+            self.draw_play_test().await;
+
+            self.draw_discard_prompt();
+
+            return;
+        }
+
+        let camera_offset = self.camera.offset();
+
+        // the level is drawn zoomed, menus and panels are drawn on top at their normal size
+        set_camera(&self.camera.macroquad_camera());
+
+        self.draw_grid();
+
+        self.draw_bounds();
+
+        for id in self.structure_ids() {
+            let structure = self.level.structures.get_mut(&id).unwrap();
+
+            structure.draw_textured(&camera_offset, &mut self.level.space, &self.textures).await;
+
+            structure.draw_resize_handles(&self.camera, &mut self.level.space);
+        }
+
+        self.draw_guides();
+
+        self.draw_collaborators();
 
-            match &structure.menu {
-                Some(menu) => menu.draw().await,
-                None => {},
+        self.draw_selection_box();
+
+        self.draw_shape_preview();
+
+        set_default_camera();
+
+        for id in self.structure_ids() {
+            if let Some(menu) = &self.level.structures[&id].menu {
+                menu.draw().await;
             }
         }
 
+        self.draw_toolbox();
+
+        self.draw_inspector();
+
+        self.draw_hud();
     }
 
     pub async fn run(&mut self) {
 
+        // closing the window asks first if there are unsaved changes
+        prevent_quit();
+
         loop {
-            self.tick();
+            self.input.update();
+
+            if let Some(asset_watcher) = &mut self.asset_watcher {
+                asset_watcher.reload_changed(&mut self.textures, &mut self.sounds).await;
+            }
+
+            if is_quit_requested() {
+                self.request_quit();
+            }
+
+            // dialogs have to be closed before playing so they dont sit there half finished
+            let dialog_open = self.file_dialog.is_some() || self.discard_prompt.is_some() || self.numeric_entry.is_some() || self.inspector.editing.is_some();
+
+            if self.input.state.is_pressed(Action::PlayTest) && !dialog_open {
+                self.toggle_play_test();
+            }
+
+            let mut editing = false;
+
+            if self.play_test.is_some() {
+                debug_span!("tick_play_test").in_scope(|| {
+                    self.tick_play_test();
+                });
+
+                // quitting still needs the discard prompt
+                self.update_dialogs();
+            }
+
+            // dialogs take over the keyboard while they are open
+            else if !self.update_dialogs() {
+                self.handle_file_actions();
+
+                debug_span!("tick").in_scope(|| {
+                    self.tick();
+                });
+
+                editing = true;
+            }
+
+            // this clones the whole level including its space, so it is only done once a frame and shared.
+            // undo, redo and other editors' changes land after it and get picked up next frame
+            let snapshot = self.level.without_editor_state();
+
+            if editing {
+                debug_span!("history").in_scope(|| {
+                    self.update_history(&snapshot);
+                });
+            }
+
+            // this runs while play testing too so the level is up to date when we stop
+            debug_span!("collaboration").in_scope(|| {
+                self.update_collaboration(&snapshot);
+            });
+
+            if self.quit {
+                return;
+            }
+
+            self.update_dirty(&snapshot);
+
+            self.autosave();
 
-            self.draw().await;
+            self.draw().instrument(debug_span!("draw")).await;
 
             macroquad::window::next_frame().await;
         }
@@ -0,0 +1,158 @@
+use macroquad::{color::{Color, WHITE, YELLOW}, input::{get_char_pressed, is_key_pressed, KeyCode}, shapes::draw_rectangle, text::draw_text, window::{screen_height, screen_width}};
+
+pub const LEVELS_DIRECTORY: &str = "levels";
+pub const LEVEL_EXTENSION: &str = "json";
+
+const LINE_HEIGHT: f32 = 24.;
+
+#[derive(Clone, Copy, PartialEq)]
+pub enum FileDialogMode {
+    Open,
+    SaveAs
+}
+
+pub enum FileDialogResult {
+    // still picking
+    Pending,
+    Cancelled,
+    // the path of the chosen level file
+    Chosen(String)
+}
+
+// lists the level files in levels/ so one can be picked to open, or a new name typed to save as
+pub struct FileDialog {
+    pub mode: FileDialogMode,
+    pub files: Vec<String>,
+    pub selected: usize,
+    // the name typed when saving
+    pub name: String
+}
+
+impl FileDialog {
+
+    pub fn new(mode: FileDialogMode) -> Self {
+
+        // throw away whatever was typed to open the dialog so it doesnt end up in the name
+        while get_char_pressed().is_some() {}
+
+        Self {
+            mode,
+            files: level_files(),
+            selected: 0,
+            name: String::new(),
+        }
+    }
+
+    pub fn update(&mut self) -> FileDialogResult {
+
+        if is_key_pressed(KeyCode::Escape) {
+            return FileDialogResult::Cancelled
+        }
+
+        match self.mode {
+            FileDialogMode::Open => self.update_open(),
+            FileDialogMode::SaveAs => self.update_save_as(),
+        }
+    }
+
+    fn update_open(&mut self) -> FileDialogResult {
+
+        if self.files.is_empty() {
+            return FileDialogResult::Pending
+        }
+
+        if is_key_pressed(KeyCode::Up) {
+            self.selected = (self.selected + self.files.len() - 1) % self.files.len();
+        }
+
+        if is_key_pressed(KeyCode::Down) {
+            self.selected = (self.selected + 1) % self.files.len();
+        }
+
+        if is_key_pressed(KeyCode::Enter) {
+            return FileDialogResult::Chosen(level_path(&self.files[self.selected]))
+        }
+
+        FileDialogResult::Pending
+    }
+
+    fn update_save_as(&mut self) -> FileDialogResult {
+
+        while let Some(character) = get_char_pressed() {
+            // keep names safe to use as file names on every platform
+            if character.is_alphanumeric() || character == '_' || character == '-' {
+                self.name.push(character);
+            }
+        }
+
+        if is_key_pressed(KeyCode::Backspace) {
+            self.name.pop();
+        }
+
+        if is_key_pressed(KeyCode::Enter) && !self.name.is_empty() {
+            return FileDialogResult::Chosen(level_path(&self.name))
+        }
+
+        FileDialogResult::Pending
+    }
+
+    pub fn draw(&self) {
+
+        draw_rectangle(0., 0., screen_width(), screen_height(), Color::new(0., 0., 0., 0.8));
+
+        match self.mode {
+            FileDialogMode::Open => {
+                draw_text("Open level", 40., 50., 40., WHITE);
+                draw_text("up/down to pick, enter to open, escape to cancel", 40., 80., 20., WHITE);
+
+                if self.files.is_empty() {
+                    draw_text(&format!("no levels in {}/", LEVELS_DIRECTORY), 40., 120., 22., WHITE);
+                }
+
+                for (index, file) in self.files.iter().enumerate() {
+
+                    let color = match index == self.selected {
+                        true => YELLOW,
+                        false => WHITE,
+                    };
+
+                    draw_text(file, 40., 120. + LINE_HEIGHT * index as f32, 22., color);
+                }
+            },
+            FileDialogMode::SaveAs => {
+                draw_text("Save level as", 40., 50., 40., WHITE);
+                draw_text("type a name, enter to save, escape to cancel", 40., 80., 20., WHITE);
+
+                draw_text(&format!("{}/{}_.{}", LEVELS_DIRECTORY, self.name, LEVEL_EXTENSION), 40., 120., 22., YELLOW);
+
+                // show what is already there so it is obvious when a save will overwrite something
+                for (index, file) in self.files.iter().enumerate() {
+                    draw_text(file, 40., 160. + LINE_HEIGHT * index as f32, 22., WHITE);
+                }
+            },
+        }
+    }
+}
+
+pub fn level_path(name: &str) -> String {
+    format!("{}/{}.{}", LEVELS_DIRECTORY, name, LEVEL_EXTENSION)
+}
+
+// the names of every level in levels/ without the extension, sorted
+pub fn level_files() -> Vec<String> {
+
+    let entries = match std::fs::read_dir(LEVELS_DIRECTORY) {
+        Ok(entries) => entries,
+        Err(_) => return vec![], // nothing has been saved yet
+    };
+
+    let mut files: Vec<String> = entries.flatten()
+        .map(|entry| entry.path())
+        .filter(|path| path.is_file() && path.extension().is_some_and(|extension| extension == LEVEL_EXTENSION))
+        .filter_map(|path| path.file_stem().map(|stem| stem.to_string_lossy().to_string()))
+        .collect();
+
+    files.sort();
+
+    files
+}
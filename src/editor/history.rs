@@ -0,0 +1,284 @@
+use diff::Diff;
+use liquidators_lib::{input::Action, level::{Level, LevelDiff}};
+
+use crate::{collaboration::diff_touches, editor::Editor};
+
+// old steps get dropped past this so long sessions dont eat all the memory
+const MAX_STEPS: usize = 200;
+
+// one undoable change. both directions are kept so the same step can be undone and then redone
+pub struct HistoryStep {
+    pub undo: LevelDiff,
+    pub redo: LevelDiff
+}
+
+// undo and redo built on level diffs. edits are recorded by comparing the level against how it looked after the last recorded step,
+// so anything that changes the level is undoable without every editor action having to report itself
+pub struct History {
+    pub undo_stack: Vec<HistoryStep>,
+    pub redo_stack: Vec<HistoryStep>,
+    // the level as of the last recorded step, without selections or drags
+    pub baseline: Level
+}
+
+impl History {
+
+    pub fn new(level: &Level) -> Self {
+        Self {
+            undo_stack: vec![],
+            redo_stack: vec![],
+            baseline: level.without_editor_state(),
+        }
+    }
+
+    // call once a frame with the level without editor state. while an edit is still in progress, like a drag or holding rotate,
+    // nothing is recorded so the whole thing becomes one step once it finishes
+    pub fn record(&mut self, current: &Level, edit_in_progress: bool) {
+
+        if edit_in_progress {
+            return;
+        }
+
+        if *current == self.baseline {
+            return;
+        }
+
+        self.undo_stack.push(HistoryStep {
+            undo: current.diff(&self.baseline),
+            redo: self.baseline.diff(current),
+        });
+
+        if self.undo_stack.len() > MAX_STEPS {
+            self.undo_stack.remove(0);
+        }
+
+        // a new edit means the undone steps cant be redone anymore
+        self.redo_stack.clear();
+
+        self.baseline = current.clone();
+    }
+
+    // returns false if there was nothing to undo
+    pub fn undo(&mut self, level: &mut Level) -> bool {
+
+        let step = match self.undo_stack.pop() {
+            Some(step) => step,
+            None => return false,
+        };
+
+        level.apply(&step.undo);
+
+        self.baseline = level.without_editor_state();
+
+        self.redo_stack.push(step);
+
+        true
+    }
+
+    pub fn redo(&mut self, level: &mut Level) -> bool {
+
+        let step = match self.redo_stack.pop() {
+            Some(step) => step,
+            None => return false,
+        };
+
+        level.apply(&step.redo);
+
+        self.baseline = level.without_editor_state();
+
+        self.undo_stack.push(step);
+
+        true
+    }
+}
+
+impl Editor {
+
+    // drags, resizes and held rotations or physics steps are still going, so they shouldnt be split into lots of tiny steps
+    pub fn edit_in_progress(&self) -> bool {
+        self.input.state.is_down(Action::RotateSelected)
+            || self.input.state.is_down(Action::StepPhysics)
+            || self.level.structures.values().any(|structure| structure.dragging || structure.resize_anchor.is_some())
+    }
+
+    pub fn update_history(&mut self, snapshot: &Level) {
+
+        let edit_in_progress = self.edit_in_progress();
+
+        // record first so an edit made this frame is what gets undone
+        self.history.record(snapshot, edit_in_progress);
+
+        if edit_in_progress {
+            return;
+        }
+
+        // steps that would change something another editor is holding have to wait until they let go of it
+        let locked = self.locked_by_others();
+
+        // redo has to be checked first because one of its bindings includes the undo binding
+        if self.input.state.is_pressed(Action::Redo) {
+
+            if self.history.redo_stack.last().is_some_and(|step| diff_touches(&self.level, &step.redo, &locked)) {
+                self.set_status("cant redo, someone else is editing what it changes".to_string());
+
+                return;
+            }
+
+            match self.history.redo(&mut self.level) {
+                true => self.set_status("redo".to_string()),
+                false => self.set_status("nothing to redo".to_string()),
+            }
+        }
+
+        else if self.input.state.is_pressed(Action::Undo) {
+
+            if self.history.undo_stack.last().is_some_and(|step| diff_touches(&self.level, &step.undo, &locked)) {
+                self.set_status("cant undo, someone else is editing what it changes".to_string());
+
+                return;
+            }
+
+            match self.history.undo(&mut self.level) {
+                true => self.set_status("undo".to_string()),
+                false => self.set_status("nothing to undo".to_string()),
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use gamelibrary::{collider::Collider, proxies::macroquad::{color::colors::RED, math::vec2::Vec2}, rigid_body::{RigidBody, RigidBodyType}};
+    use liquidators_lib::structure::{Shape, Structure};
+
+    use super::*;
+
+    fn insert_structure(level: &mut Level, id: &str, position: Vec2) {
+
+        let rigid_body_handle = level.space.insert_rigid_body(
+            RigidBody {
+                position,
+                rotation: 0.,
+                angular_velocity: 0.,
+                velocity: Vec2::ZERO,
+                body_type: RigidBodyType::Fixed,
+                owner: "host".to_string(),
+                collider: Collider {
+                    hx: 10.,
+                    hy: 10.,
+                    restitution: 0.,
+                    mass: 10.,
+                    owner: "host".to_string()
+                }
+            }
+        );
+
+        level.structures.insert(id.to_string(), Structure {
+            owner: "host".to_string(),
+            rigid_body_handle,
+            color: RED,
+            menu: None,
+            selected: false,
+            dragging: false,
+            drag_offset: None,
+            resize_anchor: None,
+            texture: None,
+            tags: vec![],
+            shape: Shape::Rectangle,
+            friction: 0.,
+            kinematic: false
+        });
+    }
+
+    fn move_structure(level: &mut Level, id: &str, position: Vec2) {
+
+        let handle = level.structures[id].rigid_body_handle.clone();
+
+        level.space.get_rigid_body_mut(&handle).unwrap().position = position;
+    }
+
+    #[test]
+    fn undo_and_redo_go_back_and_forth_between_edits() {
+
+        let mut level = Level::empty();
+
+        let mut history = History::new(&level);
+
+        insert_structure(&mut level, "a", Vec2::new(100., 100.));
+
+        history.record(&level.without_editor_state(), false);
+
+        let placed = level.without_editor_state();
+
+        move_structure(&mut level, "a", Vec2::new(250., 40.));
+
+        history.record(&level.without_editor_state(), false);
+
+        let moved = level.without_editor_state();
+
+        assert_eq!(history.undo_stack.len(), 2);
+
+        assert!(history.undo(&mut level));
+        assert!(level == placed);
+
+        assert!(history.undo(&mut level));
+        assert!(level.structures.is_empty());
+
+        assert!(!history.undo(&mut level));
+
+        assert!(history.redo(&mut level));
+        assert!(history.redo(&mut level));
+        assert!(level == moved);
+
+        assert!(!history.redo(&mut level));
+    }
+
+    #[test]
+    fn edits_in_progress_become_one_step() {
+
+        let mut level = Level::empty();
+
+        insert_structure(&mut level, "a", Vec2::new(0., 0.));
+
+        let mut history = History::new(&level);
+
+        for x in 1..10 {
+            move_structure(&mut level, "a", Vec2::new(x as f32 * 10., 0.));
+
+            history.record(&level.without_editor_state(), true);
+        }
+
+        assert!(history.undo_stack.is_empty());
+
+        history.record(&level.without_editor_state(), false);
+
+        assert_eq!(history.undo_stack.len(), 1);
+
+        assert!(history.undo(&mut level));
+
+        let handle = level.structures["a"].rigid_body_handle.clone();
+
+        assert_eq!(level.space.get_rigid_body_mut(&handle).unwrap().position.x, 0.);
+    }
+
+    #[test]
+    fn a_new_edit_clears_redo() {
+
+        let mut level = Level::empty();
+
+        let mut history = History::new(&level);
+
+        insert_structure(&mut level, "a", Vec2::new(0., 0.));
+
+        history.record(&level.without_editor_state(), false);
+
+        history.undo(&mut level);
+
+        insert_structure(&mut level, "b", Vec2::new(50., 0.));
+
+        history.record(&level.without_editor_state(), false);
+
+        assert!(history.redo_stack.is_empty());
+        assert!(!history.redo(&mut level));
+    }
+}
@@ -0,0 +1,335 @@
+use gamelibrary::{proxies::macroquad::math::vec2::Vec2, rigid_body::RigidBodyType};
+use liquidators_lib::input::Action;
+use macroquad::{color::{Color, GRAY, WHITE, YELLOW}, input::{get_char_pressed, is_key_pressed, KeyCode}, shapes::draw_rectangle, text::draw_text, window::screen_width};
+
+use crate::editor::Editor;
+
+const PANEL_WIDTH: f32 = 300.;
+// leaves room for the toolbox above it
+const PANEL_Y: f32 = 190.;
+const LINE_HEIGHT: f32 = 24.;
+
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub enum InspectorField {
+    Color,
+    BodyType,
+    Mass,
+    Restitution,
+    Friction,
+    VelocityX,
+    VelocityY,
+    AngularVelocity,
+    Texture,
+    Tags
+}
+
+impl InspectorField {
+    pub const ALL: [InspectorField; 10] = [
+        InspectorField::Color,
+        InspectorField::BodyType,
+        InspectorField::Mass,
+        InspectorField::Restitution,
+        InspectorField::Friction,
+        InspectorField::VelocityX,
+        InspectorField::VelocityY,
+        InspectorField::AngularVelocity,
+        InspectorField::Texture,
+        InspectorField::Tags,
+    ];
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            InspectorField::Color => "color (r, g, b, a)",
+            InspectorField::BodyType => "body type",
+            InspectorField::Mass => "mass",
+            InspectorField::Restitution => "restitution",
+            InspectorField::Friction => "friction",
+            InspectorField::VelocityX => "velocity x",
+            InspectorField::VelocityY => "velocity y",
+            InspectorField::AngularVelocity => "angular velocity",
+            InspectorField::Texture => "texture",
+            InspectorField::Tags => "tags",
+        }
+    }
+}
+
+// a field being typed into
+pub struct InspectorEdit {
+    // the structure being edited
+    pub id: String,
+    pub field: InspectorField,
+    pub text: String
+}
+
+// shows the properties of the selected structure down the right side. clicking a property edits it
+pub struct Inspector {
+    pub editing: Option<InspectorEdit>
+}
+
+impl Inspector {
+    pub fn new() -> Self {
+        Self {
+            editing: None,
+        }
+    }
+}
+
+impl Default for Inspector {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+fn parse_number(text: &str) -> Result<f32, String> {
+    match text.trim().parse::<f32>() {
+        Ok(number) if number.is_finite() => Ok(number),
+        _ => Err(format!("{} isnt a number", text)),
+    }
+}
+
+impl Editor {
+
+    // the inspector only shows up with exactly one structure selected
+    pub fn inspected_id(&self) -> Option<String> {
+        match self.selected_ids().as_slice() {
+            [id] => Some(id.clone()),
+            _ => None,
+        }
+    }
+
+    // clicks on the panel shouldnt also select or place things behind it
+    pub fn mouse_over_inspector(&self) -> bool {
+
+        if self.inspected_id().is_none() {
+            return false;
+        }
+
+        let mouse_position = &self.input.state.mouse_position;
+
+        let height = LINE_HEIGHT * (InspectorField::ALL.len() + 1) as f32 + 10.;
+
+        mouse_position.x >= screen_width() - PANEL_WIDTH - 10. && mouse_position.y >= PANEL_Y && mouse_position.y <= PANEL_Y + height
+    }
+
+    // the current value of a field as it is shown and first typed into
+    pub fn inspector_value(&mut self, id: &String, field: InspectorField) -> String {
+
+        let structure = &self.level.structures[id];
+
+        let rigid_body = self.level.space.get_rigid_body_mut(&structure.rigid_body_handle).unwrap();
+
+        match field {
+            InspectorField::Color => format!("{:.2}, {:.2}, {:.2}, {:.2}", structure.color.r, structure.color.g, structure.color.b, structure.color.a),
+            InspectorField::BodyType if structure.kinematic => "kinematic".to_string(),
+            InspectorField::BodyType => match rigid_body.body_type {
+                RigidBodyType::Dynamic => "dynamic".to_string(),
+                RigidBodyType::Fixed => "fixed".to_string(),
+                _ => "kinematic".to_string(),
+            },
+            InspectorField::Mass => format!("{:.2}", rigid_body.collider.mass),
+            InspectorField::Restitution => format!("{:.2}", rigid_body.collider.restitution),
+            InspectorField::Friction => format!("{:.2}", structure.friction),
+            InspectorField::VelocityX => format!("{:.2}", rigid_body.velocity.x),
+            InspectorField::VelocityY => format!("{:.2}", rigid_body.velocity.y),
+            InspectorField::AngularVelocity => format!("{:.2}", rigid_body.angular_velocity),
+            InspectorField::Texture => structure.texture.clone().unwrap_or_default(),
+            InspectorField::Tags => structure.tags.join(", "),
+        }
+    }
+
+    // called while nothing is being typed, picks which field to edit
+    pub fn update_inspector(&mut self) {
+
+        let id = match self.inspected_id() {
+            Some(id) => id,
+            None => return,
+        };
+
+        if !self.input.state.is_pressed(Action::SelectStructure) || !self.mouse_over_inspector() {
+            return;
+        }
+
+        let row = ((self.input.state.mouse_position.y - PANEL_Y) / LINE_HEIGHT).floor() as usize;
+
+        // the first row is the title
+        let field = match row.checked_sub(1).and_then(|index| InspectorField::ALL.get(index)) {
+            Some(field) => *field,
+            None => return,
+        };
+
+        // clicking goes from dynamic to fixed to kinematic and back to dynamic
+        if field == InspectorField::BodyType {
+
+            let structure = self.level.structures.get_mut(&id).unwrap();
+
+            let rigid_body = self.level.space.get_rigid_body_mut(&structure.rigid_body_handle).unwrap();
+
+            let dynamic = matches!(rigid_body.body_type, RigidBodyType::Dynamic);
+
+            match (dynamic, structure.kinematic) {
+                (_, true) => {
+                    structure.kinematic = false;
+                    rigid_body.body_type = RigidBodyType::Dynamic;
+                },
+                (true, false) => rigid_body.body_type = RigidBodyType::Fixed,
+                // kinematic structures stay fixed in the space so collisions cant push them
+                (_, false) => {
+                    structure.kinematic = true;
+                    rigid_body.body_type = RigidBodyType::Fixed;
+                },
+            }
+
+            return;
+        }
+
+        // throw away the click's typing so far so it doesnt end up in the field
+        while get_char_pressed().is_some() {}
+
+        let text = self.inspector_value(&id, field);
+
+        self.inspector.editing = Some(InspectorEdit { id, field, text });
+    }
+
+    // called from update_dialogs while a field is being typed into
+    pub fn update_inspector_edit(&mut self) {
+
+        let edit = match &mut self.inspector.editing {
+            Some(edit) => edit,
+            None => return,
+        };
+
+        if is_key_pressed(KeyCode::Escape) {
+            self.inspector.editing = None;
+
+            return;
+        }
+
+        while let Some(character) = get_char_pressed() {
+            if !character.is_control() {
+                edit.text.push(character);
+            }
+        }
+
+        if is_key_pressed(KeyCode::Backspace) {
+            edit.text.pop();
+        }
+
+        if !is_key_pressed(KeyCode::Enter) {
+            return;
+        }
+
+        let edit = self.inspector.editing.take().unwrap();
+
+        if let Err(error) = self.apply_inspector_edit(&edit) {
+            self.set_status(error);
+
+            // keep the field open so the typo can be fixed
+            self.inspector.editing = Some(edit);
+        }
+    }
+
+    // changes go straight into the level's space so they show up right away
+    pub fn apply_inspector_edit(&mut self, edit: &InspectorEdit) -> Result<(), String> {
+
+        // the structure could have been deleted while the field was open
+        let structure = match self.level.structures.get_mut(&edit.id) {
+            Some(structure) => structure,
+            None => return Ok(()),
+        };
+
+        let rigid_body = self.level.space.get_rigid_body_mut(&structure.rigid_body_handle).unwrap();
+
+        match edit.field {
+            InspectorField::Color => {
+
+                let channels = edit.text.split(',').map(parse_number).collect::<Result<Vec<f32>, String>>()?;
+
+                let (r, g, b, a) = match channels.as_slice() {
+                    [r, g, b] => (*r, *g, *b, 1.),
+                    [r, g, b, a] => (*r, *g, *b, *a),
+                    _ => return Err("colors are 3 or 4 numbers from 0 to 1".to_string()),
+                };
+
+                structure.color.r = r.clamp(0., 1.);
+                structure.color.g = g.clamp(0., 1.);
+                structure.color.b = b.clamp(0., 1.);
+                structure.color.a = a.clamp(0., 1.);
+            },
+            InspectorField::BodyType => {},
+            InspectorField::Mass => {
+
+                let mass = parse_number(&edit.text)?;
+
+                if mass <= 0. {
+                    return Err("mass has to be more than 0".to_string());
+                }
+
+                rigid_body.collider.mass = mass;
+            },
+            InspectorField::Restitution => rigid_body.collider.restitution = parse_number(&edit.text)?.max(0.),
+            InspectorField::Friction => structure.friction = parse_number(&edit.text)?.clamp(0., 1.),
+            InspectorField::VelocityX => rigid_body.velocity = Vec2::new(parse_number(&edit.text)?, rigid_body.velocity.y),
+            InspectorField::VelocityY => rigid_body.velocity = Vec2::new(rigid_body.velocity.x, parse_number(&edit.text)?),
+            InspectorField::AngularVelocity => rigid_body.angular_velocity = parse_number(&edit.text)?,
+            InspectorField::Texture => {
+                structure.texture = match edit.text.trim() {
+                    "" => None,
+                    texture => Some(texture.to_string()),
+                };
+            },
+            InspectorField::Tags => {
+                structure.tags = edit.text.split(',')
+                    .map(|tag| tag.trim().to_string())
+                    .filter(|tag| !tag.is_empty())
+                    .collect();
+            },
+        }
+
+        Ok(())
+    }
+
+    pub fn draw_inspector(&mut self) {
+
+        let id = match self.inspected_id() {
+            Some(id) => id,
+            None => return,
+        };
+
+        let x = screen_width() - PANEL_WIDTH - 10.;
+
+        draw_rectangle(x, PANEL_Y, PANEL_WIDTH, LINE_HEIGHT * (InspectorField::ALL.len() + 1) as f32 + 10., Color::new(0., 0., 0., 0.6));
+
+        draw_text("Properties", x + 10., PANEL_Y + LINE_HEIGHT, 22., WHITE);
+
+        for (index, field) in InspectorField::ALL.iter().enumerate() {
+
+            let editing = self.inspector.editing.as_ref()
+                .filter(|edit| edit.id == id && edit.field == *field)
+                .map(|edit| edit.text.clone());
+
+            let (value, color) = match editing {
+                Some(text) => (format!("{}_", text), YELLOW),
+                None => (self.inspector_value(&id, *field), WHITE),
+            };
+
+            let value = match value.is_empty() {
+                true => "none".to_string(),
+                false => value,
+            };
+
+            let y = PANEL_Y + LINE_HEIGHT * (index + 2) as f32;
+
+            draw_text(field.name(), x + 10., y, 18., GRAY);
+            draw_text(&value, x + 150., y, 18., color);
+        }
+
+        // a texture that isnt loaded is easy to miss since the structure just keeps its color
+        let structure = &self.level.structures[&id];
+
+        if let Some(texture) = &structure.texture {
+            if !self.textures.contains_key(texture) {
+                draw_text("texture isnt loaded", x + 10., PANEL_Y + LINE_HEIGHT * (InspectorField::ALL.len() + 2) as f32, 18., YELLOW);
+            }
+        }
+    }
+}
@@ -1,9 +1,30 @@
+use std::collections::HashMap;
+
+use collaboration::Collaboration;
 use editor::Editor;
-use gamelibrary::space::Space;
-use liquidators_lib::level::Level;
-use macroquad::{miniquad::conf::Platform, window::Conf};
+use history::History;
+use inspector::Inspector;
+use selection::Selection;
+use snapping::Snapping;
+use toolbox::Toolbox;
+use gamelibrary::{proxies::macroquad::math::vec2::Vec2, time::Time};
+use liquidators_lib::{args::arg_value, assets::{AssetManager, AssetWatcher}, camera::Camera, input::{Input, DEFAULT_BINDINGS_PATH}, level::Level, logging};
+use tracing::error;
+use macroquad::{miniquad::conf::Platform, window::{screen_height, screen_width, Conf}};
 
+pub mod bounds;
+pub mod collaboration;
 pub mod editor;
+pub mod file_dialog;
+pub mod history;
+pub mod inspector;
+pub mod numeric_entry;
+pub mod play_test;
+pub mod saving;
+pub mod selection;
+pub mod snapping;
+pub mod toolbox;
+pub mod view;
 
 fn window_conf() -> Conf {
     let mut conf = Conf {
@@ -21,16 +42,73 @@ fn window_conf() -> Conf {
 #[macroquad::main(window_conf)]
 async fn main() {
 
-    let level = Level { 
-        structures: vec![],
-        space: Space::new(-980.)
-    };
+    let args: Vec<String> = std::env::args().collect();
+
+    // keep this alive until the editor exits or we lose buffered file logs
+    let _log_guard = logging::init("editor", arg_value(&args, "--log-filter"), arg_value(&args, "--log-dir"));
+
+    let level = Level::empty();
 
     
     let mut editor = Editor { 
-        level
+        saved_level: level.clone(),
+        history: History::new(&level),
+        toolbox: Toolbox::new(),
+        selection: Selection::new(),
+        snapping: Snapping::new(),
+        numeric_entry: None,
+        inspector: Inspector::new(),
+        camera: Camera::new(Vec2::new(screen_width() / 2., screen_height() / 2.)),
+        pan_last: None,
+        play_test: None,
+        collaboration: None,
+        level,
+        textures: HashMap::new(),
+        sounds: HashMap::new(),
+        asset_watcher: None,
+        input: Input::new(&arg_value(&args, "--bindings").unwrap_or(DEFAULT_BINDINGS_PATH.to_string())),
+        level_path: None,
+        dirty: false,
+        file_dialog: None,
+        discard_prompt: None,
+        last_autosave: Time::now(),
+        status: None,
+        quit: false
     };
 
+    // edit together with everyone else connected to the same server, like --connect ws://localhost:5556 --name Sam
+    if let Some(url) = arg_value(&args, "--connect") {
+        match Collaboration::connect(&url, arg_value(&args, "--name")) {
+            Ok((collaboration, level)) => editor.join_collaboration(collaboration, level),
+            Err(error) => error!(%error, "failed to connect to the server, editing on our own"),
+        }
+    }
+
+    // pick up where we left off with --level <file>. when editing together this replaces the shared level for everyone
+    if let Some(level_path) = arg_value(&args, "--level") {
+        editor.open_level(&level_path);
+    }
+
+    let mut assets = AssetManager::discover();
+
+    assets.load_with_loading_screen(&mut editor.textures, &mut editor.sounds).await;
+
+    for (key, reason) in &assets.failed {
+        error!(%key, %reason, "missing asset");
+    }
+
+    // reload assets as they are saved with --watch-assets
+    if args.contains(&"--watch-assets".to_string()) {
+        editor.asset_watcher = match AssetWatcher::start() {
+            Ok(asset_watcher) => Some(asset_watcher),
+            Err(error) => {
+                error!(%error, "failed to watch assets");
+
+                None
+            },
+        };
+    }
+
     editor.run().await;
     
     
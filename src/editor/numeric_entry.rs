@@ -0,0 +1,160 @@
+use gamelibrary::proxies::macroquad::math::vec2::Vec2;
+use liquidators_lib::structure::MIN_HALF_EXTENT;
+use macroquad::{color::{Color, WHITE, YELLOW}, input::{get_char_pressed, is_key_pressed, KeyCode}, shapes::draw_rectangle, text::draw_text, window::{screen_height, screen_width}};
+
+use crate::editor::Editor;
+
+pub const FIELDS: [&str; 5] = ["x", "y", "rotation (degrees)", "width", "height"];
+
+const LINE_HEIGHT: f32 = 26.;
+
+// typing in the exact position, rotation and size of the selected structure
+pub struct NumericEntry {
+    // the structure being edited
+    pub id: String,
+    // what has been typed into each of FIELDS
+    pub values: [String; 5],
+    pub field: usize
+}
+
+impl Editor {
+
+    // only works with exactly one structure selected since the numbers are for a single structure
+    pub fn open_numeric_entry(&mut self) {
+
+        let id = match self.selected_ids().as_slice() {
+            [id] => id.clone(),
+            _ => {
+                self.set_status("select a single structure to type in its transform".to_string());
+
+                return;
+            },
+        };
+
+        let handle = self.level.structures[&id].rigid_body_handle.clone();
+
+        let rigid_body = self.level.space.get_rigid_body_mut(&handle).unwrap();
+
+        let values = [
+            rigid_body.position.x,
+            rigid_body.position.y,
+            rigid_body.rotation.to_degrees(),
+            rigid_body.collider.hx * 2.,
+            rigid_body.collider.hy * 2.
+        ].map(|value| format!("{:.1}", value));
+
+        // throw away the key that opened the entry so it doesnt end up in the first field
+        while get_char_pressed().is_some() {}
+
+        self.numeric_entry = Some(NumericEntry { id, values, field: 0 });
+    }
+
+    pub fn update_numeric_entry(&mut self) {
+
+        let numeric_entry = match &mut self.numeric_entry {
+            Some(numeric_entry) => numeric_entry,
+            None => return,
+        };
+
+        if is_key_pressed(KeyCode::Escape) {
+            self.numeric_entry = None;
+
+            return;
+        }
+
+        if is_key_pressed(KeyCode::Up) {
+            numeric_entry.field = (numeric_entry.field + FIELDS.len() - 1) % FIELDS.len();
+        }
+
+        if is_key_pressed(KeyCode::Down) || is_key_pressed(KeyCode::Tab) {
+            numeric_entry.field = (numeric_entry.field + 1) % FIELDS.len();
+        }
+
+        while let Some(character) = get_char_pressed() {
+            if character.is_ascii_digit() || character == '.' || character == '-' {
+                numeric_entry.values[numeric_entry.field].push(character);
+            }
+        }
+
+        if is_key_pressed(KeyCode::Backspace) {
+            numeric_entry.values[numeric_entry.field].pop();
+        }
+
+        if is_key_pressed(KeyCode::Enter) {
+            self.apply_numeric_entry();
+        }
+    }
+
+    fn apply_numeric_entry(&mut self) {
+
+        let (id, values) = match &self.numeric_entry {
+            Some(numeric_entry) => (numeric_entry.id.clone(), numeric_entry.values.clone()),
+            None => return,
+        };
+
+        let mut numbers = [0.; 5];
+
+        for (index, value) in values.iter().enumerate() {
+            match value.parse::<f32>() {
+                Ok(number) if number.is_finite() => numbers[index] = number,
+                _ => {
+                    self.set_status(format!("{} isnt a number", FIELDS[index]));
+
+                    return;
+                },
+            }
+        }
+
+        let [x, y, rotation, width, height] = numbers;
+
+        // the structure could have been deleted while the entry was open
+        let handle = match self.level.structures.get(&id) {
+            Some(structure) => structure.rigid_body_handle.clone(),
+            None => {
+                self.numeric_entry = None;
+
+                return;
+            },
+        };
+
+        let rigid_body = self.level.space.get_rigid_body_mut(&handle).unwrap();
+
+        rigid_body.position = Vec2::new(x, y);
+        rigid_body.rotation = rotation.to_radians();
+        rigid_body.collider.hx = (width / 2.).max(MIN_HALF_EXTENT);
+        rigid_body.collider.hy = (height / 2.).max(MIN_HALF_EXTENT);
+
+        self.numeric_entry = None;
+    }
+
+    pub fn draw_numeric_entry(&self) {
+
+        let numeric_entry = match &self.numeric_entry {
+            Some(numeric_entry) => numeric_entry,
+            None => return,
+        };
+
+        let (width, height) = (360., LINE_HEIGHT * (FIELDS.len() + 2) as f32);
+        let (x, y) = ((screen_width() - width) / 2., (screen_height() - height) / 2.);
+
+        draw_rectangle(x, y, width, height, Color::new(0., 0., 0., 0.85));
+
+        draw_text("up/down to pick, enter to apply, escape to cancel", x + 10., y + LINE_HEIGHT, 18., WHITE);
+
+        for (index, field) in FIELDS.iter().enumerate() {
+
+            let (color, cursor) = match index == numeric_entry.field {
+                true => (YELLOW, "_"),
+                false => (WHITE, ""),
+            };
+
+            draw_text(
+                &format!("{}: {}{}", field, numeric_entry.values[index], cursor),
+                x + 10.,
+                y + LINE_HEIGHT * (index + 2) as f32,
+                22.,
+                color
+            );
+        }
+    }
+}
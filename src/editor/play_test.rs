@@ -0,0 +1,132 @@
+use gamelibrary::time::Time;
+use liquidators_lib::{camera::Camera, entities::{tick_entities, Entity}, game_state::GameState, input::Action, player::Player, sound::{SoundEvent, SoundMix, SoundPlayer}, TickContext};
+use macroquad::{camera::{set_camera, set_default_camera}, color::WHITE, text::draw_text};
+use tracing::info;
+
+use crate::editor::Editor;
+
+// the level running as it would in the game, with a local player in it.
+// the editor's level is never touched while playing so stopping goes back to exactly how it was
+pub struct PlayTest {
+    pub game_state: GameState,
+    // the owner of everything in the play test, standing in for a client's uuid
+    pub uuid: String,
+    // the id of the player entity
+    pub player_id: String,
+    pub camera: Camera,
+    pub sound_events: Vec<SoundEvent>,
+    pub sound_player: SoundPlayer,
+    // there is no server while play testing so the editor is the host
+    pub is_host: bool,
+    pub last_tick: Time
+}
+
+impl PlayTest {
+
+    pub fn start(editor: &Editor) -> Self {
+
+        let uuid = gamelibrary::uuid();
+
+        // from_level takes its own copy of the level so the editor's one stays as the snapshot to go back to
+        let mut game_state = GameState::from_level(&editor.level, &uuid);
+
+        let player = Player::new(&mut game_state.space, game_state.bounds.spawn_point.clone(), &uuid);
+
+        let player_id = game_state.spawn(Entity::Player(player));
+
+        let mut camera = Camera::new(game_state.bounds.spawn_point.clone());
+
+        camera.bounds = game_state.bounds.camera_bounds();
+
+        Self {
+            game_state,
+            uuid,
+            player_id,
+            camera,
+            sound_events: vec![],
+            sound_player: SoundPlayer::new(SoundMix::default()),
+            is_host: true,
+            last_tick: Time::now(),
+        }
+    }
+}
+
+impl Editor {
+
+    pub fn toggle_play_test(&mut self) {
+
+        match self.play_test.take() {
+            Some(_) => {
+                info!("stopped play test");
+
+                self.set_status("stopped playing".to_string());
+            },
+            None => {
+                info!(level = %self.level_name(), "started play test");
+
+                self.play_test = Some(PlayTest::start(self));
+            },
+        }
+    }
+
+    // the same tick the client does, minus the network
+    pub fn tick_play_test(&mut self) {
+
+        let play_test = match &mut self.play_test {
+            Some(play_test) => play_test,
+            None => return,
+        };
+
+        let mut tick_context = TickContext {
+            game_state: &mut play_test.game_state,
+            is_host: &mut play_test.is_host,
+            textures: &mut self.textures,
+            sounds: &mut self.sounds,
+            sound_events: &mut play_test.sound_events,
+            time: &play_test.last_tick,
+            uuid: &play_test.uuid,
+            camera: &mut play_test.camera,
+            input: &self.input.state,
+        };
+
+        tick_entities(&mut tick_context);
+
+        play_test.game_state.space.step(&play_test.uuid);
+
+        let delta = play_test.last_tick.elapsed().num_milliseconds() as f32 / 1000.;
+
+        let player_position = play_test.game_state.entities.get(&play_test.player_id)
+            .map(|player| player.rigid_body_handle().clone())
+            .and_then(|handle| play_test.game_state.space.get_rigid_body_mut(&handle).map(|rigid_body| rigid_body.position.clone()));
+
+        if let Some(player_position) = player_position {
+            play_test.camera.follow(&player_position, delta);
+        }
+
+        play_test.camera.update(delta);
+
+        for sound_event in std::mem::take(&mut play_test.sound_events) {
+            play_test.sound_player.play(&sound_event, &self.sounds, &play_test.camera.position);
+        }
+
+        play_test.last_tick = Time::now();
+    }
+
+    pub async fn draw_play_test(&mut self) {
+
+        let play_test = match &mut self.play_test {
+            Some(play_test) => play_test,
+            None => return,
+        };
+
+        set_camera(&play_test.camera.macroquad_camera());
+
+        play_test.game_state.draw(&play_test.camera.offset(), &self.textures).await;
+
+        set_default_camera();
+
+        let bindings: Vec<String> = self.input.bindings.get(Action::PlayTest).iter().map(|binding| binding.to_string()).collect();
+
+        draw_text(&format!("Play testing {}, {} to stop", self.level_name(), bindings.join(", ")), 10., 25., 25., WHITE);
+    }
+}
@@ -0,0 +1,321 @@
+use gamelibrary::time::Time;
+use liquidators_lib::{input::Action, level::Level};
+use macroquad::input::{is_key_pressed, KeyCode};
+use tracing::{info, warn};
+
+use crate::{editor::Editor, history::History, file_dialog::{FileDialog, FileDialogMode, FileDialogResult, LEVEL_EXTENSION}};
+
+pub const AUTOSAVE_DIRECTORY: &str = "levels/autosave";
+const AUTOSAVE_INTERVAL_SECONDS: i64 = 60;
+// older backups of the same level get deleted past this
+const MAX_AUTOSAVES: usize = 10;
+// sorts in the same order as the time it was saved
+const AUTOSAVE_TIMESTAMP_FORMAT: &str = "%Y%m%d-%H%M%S";
+
+// something that would throw away unsaved changes or another level, waiting for the user to say it is fine
+#[derive(Clone)]
+pub enum DiscardPrompt {
+    Open(String),
+    Quit,
+    // saving as a level that already exists
+    Overwrite(String)
+}
+
+impl DiscardPrompt {
+    pub fn message(&self) -> String {
+        match self {
+            DiscardPrompt::Overwrite(path) => format!("{} already exists. Overwrite it? (y/n)", path),
+            _ => "You have unsaved changes. Discard them? (y/n)".to_string(),
+        }
+    }
+}
+
+impl Editor {
+
+    // the file name of the current level without the extension
+    pub fn level_name(&self) -> String {
+        match &self.level_path {
+            Some(path) => std::path::Path::new(path).file_stem().map(|stem| stem.to_string_lossy().to_string()).unwrap_or_default(),
+            None => "untitled".to_string(),
+        }
+    }
+
+    pub fn set_status(&mut self, status: String) {
+        self.status = Some((status, Time::now()));
+    }
+
+    // snapshot is the level without editor state
+    pub fn update_dirty(&mut self, snapshot: &Level) {
+        self.dirty = *snapshot != self.saved_level;
+    }
+
+    pub fn save_level(&mut self, path: &str) {
+
+        // the path might not be in levels/ if it came from --level
+        if let Some(directory) = std::path::Path::new(path).parent().filter(|directory| !directory.as_os_str().is_empty()) {
+            if let Err(error) = std::fs::create_dir_all(directory) {
+                warn!(%path, %error, "failed to create the level's directory");
+            }
+        }
+
+        let level = self.level.without_editor_state();
+
+        match level.save(path) {
+            Ok(()) => {
+                info!(%path, "saved level");
+
+                self.level_path = Some(path.to_string());
+                self.saved_level = level;
+                self.dirty = false;
+
+                self.set_status(format!("saved {}", path));
+            },
+            Err(error) => {
+                warn!(%path, %error, "failed to save level");
+
+                self.set_status(format!("failed to save {}: {}", path, error));
+            },
+        }
+    }
+
+    pub fn open_level(&mut self, path: &str) {
+
+        // opening a level while editing together replaces it for everyone, which would pull structures out from under people
+        let locked = self.locked_by_others();
+
+        if !locked.is_empty() {
+            self.set_status(format!("cant open {} while others are editing {} structures", path, locked.len()));
+
+            return;
+        }
+
+        let level = match Level::load(path) {
+            Ok(level) => level,
+            Err(error) => {
+                warn!(%path, %error, "failed to open level");
+
+                self.set_status(format!("failed to open {}: {}", path, error));
+
+                return;
+            },
+        };
+
+        info!(%path, structures = level.structures.len(), "opened level");
+
+        self.saved_level = level.without_editor_state();
+        // undoing back into the previous level would be confusing
+        self.history = History::new(&level);
+        self.level = level;
+        self.level_path = Some(path.to_string());
+        self.dirty = false;
+
+        self.set_status(format!("opened {}", path));
+    }
+
+    pub fn handle_file_actions(&mut self) {
+
+        // save as has to be checked first because its binding includes the save binding
+        if self.input.state.is_pressed(Action::SaveLevelAs) {
+            self.file_dialog = Some(FileDialog::new(FileDialogMode::SaveAs));
+        }
+
+        else if self.input.state.is_pressed(Action::SaveLevel) {
+            match self.level_path.clone() {
+                Some(path) => self.save_level(&path),
+                None => self.file_dialog = Some(FileDialog::new(FileDialogMode::SaveAs)),
+            }
+        }
+
+        if self.input.state.is_pressed(Action::OpenLevel) {
+            self.file_dialog = Some(FileDialog::new(FileDialogMode::Open));
+        }
+    }
+
+    // returns true while a dialog or prompt has the keyboard, in which case the editor shouldnt react to anything else
+    pub fn update_dialogs(&mut self) -> bool {
+
+        if let Some(discard_prompt) = self.discard_prompt.clone() {
+
+            if is_key_pressed(KeyCode::Y) {
+                self.discard_prompt = None;
+
+                match discard_prompt {
+                    DiscardPrompt::Open(path) => self.open_level(&path),
+                    DiscardPrompt::Quit => self.quit = true,
+                    DiscardPrompt::Overwrite(path) => self.save_level(&path),
+                }
+            }
+
+            if is_key_pressed(KeyCode::N) || is_key_pressed(KeyCode::Escape) {
+                self.discard_prompt = None;
+            }
+
+            return true
+        }
+
+        if self.inspector.editing.is_some() {
+            self.update_inspector_edit();
+
+            return true
+        }
+
+        if self.numeric_entry.is_some() {
+            self.update_numeric_entry();
+
+            return true
+        }
+
+        let file_dialog = match &mut self.file_dialog {
+            Some(file_dialog) => file_dialog,
+            None => return false,
+        };
+
+        match file_dialog.update() {
+            FileDialogResult::Pending => {},
+            FileDialogResult::Cancelled => self.file_dialog = None,
+            FileDialogResult::Chosen(path) => {
+
+                let mode = file_dialog.mode;
+
+                self.file_dialog = None;
+
+                match mode {
+                    // saving over the level we already have open is just saving
+                    FileDialogMode::SaveAs if std::path::Path::new(&path).exists() && self.level_path.as_ref() != Some(&path) => {
+                        self.discard_prompt = Some(DiscardPrompt::Overwrite(path))
+                    },
+                    FileDialogMode::SaveAs => self.save_level(&path),
+                    FileDialogMode::Open if self.dirty => self.discard_prompt = Some(DiscardPrompt::Open(path)),
+                    FileDialogMode::Open => self.open_level(&path),
+                }
+            },
+        }
+
+        true
+    }
+
+    pub fn request_quit(&mut self) {
+        match self.dirty {
+            true => self.discard_prompt = Some(DiscardPrompt::Quit),
+            false => self.quit = true,
+        }
+    }
+
+    // writes a timestamped backup of unsaved changes every so often, without touching the real level file
+    pub fn autosave(&mut self) {
+
+        if !self.dirty || self.last_autosave.elapsed().num_seconds() < AUTOSAVE_INTERVAL_SECONDS {
+            return;
+        }
+
+        self.last_autosave = Time::now();
+
+        if let Err(error) = std::fs::create_dir_all(AUTOSAVE_DIRECTORY) {
+            warn!(%error, "failed to create the autosave directory");
+
+            return;
+        }
+
+        let name = self.level_name();
+
+        let path = format!(
+            "{}/{}-{}.{}",
+            AUTOSAVE_DIRECTORY,
+            name,
+            chrono::Local::now().format(AUTOSAVE_TIMESTAMP_FORMAT),
+            LEVEL_EXTENSION
+        );
+
+        match self.level.without_editor_state().save(&path) {
+            Ok(()) => info!(%path, "autosaved level"),
+            Err(error) => warn!(%path, %error, "failed to autosave level"),
+        }
+
+        prune_autosaves(AUTOSAVE_DIRECTORY, &name);
+    }
+}
+
+// whether a file in the autosave directory is a backup of the named level, like "arena-20260101-120000.json".
+// the timestamp has to parse so "arena" doesnt match the backups of "arena-2"
+fn is_autosave_of(file_name: &str, name: &str) -> bool {
+
+    let timestamp = file_name.strip_prefix(name)
+        .and_then(|rest| rest.strip_prefix('-'))
+        .and_then(|rest| rest.strip_suffix(LEVEL_EXTENSION))
+        .and_then(|rest| rest.strip_suffix('.'));
+
+    timestamp.is_some_and(|timestamp| chrono::NaiveDateTime::parse_from_str(timestamp, AUTOSAVE_TIMESTAMP_FORMAT).is_ok())
+}
+
+// the timestamps sort in order so the oldest backups come first
+fn prune_autosaves(directory: &str, name: &str) {
+
+    let entries = match std::fs::read_dir(directory) {
+        Ok(entries) => entries,
+        Err(_) => return,
+    };
+
+    let mut backups: Vec<std::path::PathBuf> = entries.flatten()
+        .map(|entry| entry.path())
+        .filter(|path| path.file_name().is_some_and(|file_name| is_autosave_of(&file_name.to_string_lossy(), name)))
+        .collect();
+
+    backups.sort();
+
+    let excess = backups.len().saturating_sub(MAX_AUTOSAVES);
+
+    for backup in &backups[..excess] {
+        if let Err(error) = std::fs::remove_file(backup) {
+            warn!(path = %backup.display(), %error, "failed to remove old autosave");
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn autosaves_need_the_exact_name_and_a_timestamp() {
+
+        assert!(is_autosave_of("arena-20260101-120000.json", "arena"));
+
+        assert!(!is_autosave_of("arena-2-20260101-120000.json", "arena"));
+        assert!(!is_autosave_of("arena-notes.json", "arena"));
+        assert!(!is_autosave_of("arena-20260101-120000.txt", "arena"));
+    }
+
+    #[test]
+    fn pruning_leaves_levels_with_a_shared_prefix_alone() {
+
+        let directory = std::env::temp_dir().join(format!("autosave_test_{}", std::process::id()));
+
+        std::fs::create_dir_all(&directory).unwrap();
+
+        for minute in 0..MAX_AUTOSAVES + 3 {
+            std::fs::write(directory.join(format!("arena-20260101-12{:02}00.json", minute)), "{}").unwrap();
+        }
+
+        for minute in 0..3 {
+            std::fs::write(directory.join(format!("arena-2-20260101-11{:02}00.json", minute)), "{}").unwrap();
+        }
+
+        prune_autosaves(directory.to_str().unwrap(), "arena");
+
+        let mut remaining: Vec<String> = std::fs::read_dir(&directory).unwrap()
+            .flatten()
+            .map(|entry| entry.file_name().to_string_lossy().to_string())
+            .collect();
+
+        remaining.sort();
+
+        std::fs::remove_dir_all(&directory).unwrap();
+
+        assert_eq!(remaining.iter().filter(|file_name| file_name.starts_with("arena-2-")).count(), 3);
+        assert_eq!(remaining.iter().filter(|file_name| is_autosave_of(file_name, "arena")).count(), MAX_AUTOSAVES);
+
+        // the oldest ones went
+        assert!(!remaining.contains(&"arena-20260101-120000.json".to_string()));
+        assert!(remaining.contains(&format!("arena-20260101-12{:02}00.json", MAX_AUTOSAVES + 2)));
+    }
+}
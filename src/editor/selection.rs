@@ -0,0 +1,434 @@
+use std::collections::HashMap;
+
+use gamelibrary::{proxies::macroquad::math::vec2::Vec2, rigid_body::RigidBody, traits::HasRigidBody};
+use liquidators_lib::{input::Action, structure::{rotate_vector, Structure}};
+use macroquad::{color::{Color, WHITE}, miniquad::window::{clipboard_get, clipboard_set}, shapes::{draw_rectangle, draw_rectangle_lines}, window::screen_height};
+use serde::{Deserialize, Serialize};
+use tracing::warn;
+
+use crate::{editor::Editor, snapping::step_below};
+
+// how far duplicates are moved from the originals so they dont sit exactly on top of them
+const DUPLICATE_OFFSET: f32 = 20.;
+// radians per frame while rotate is held
+const ROTATION_SPEED: f32 = 0.05;
+
+// a structure together with its rigid body, since the structure on its own is only a handle into the level's space
+#[derive(Serialize, Deserialize, Clone)]
+pub struct ClipboardStructure {
+    pub structure: Structure,
+    pub rigid_body: RigidBody
+}
+
+// the selected structures being moved with the mouse
+pub struct Drag {
+    // where the mouse was pressed in world coordinates
+    pub mouse_start: Vec2,
+    // the structure that was clicked. it is the one snapped to the grid and lined up with others, the rest follow it
+    pub anchor: String,
+    pub start_positions: HashMap<String, Vec2>
+}
+
+pub struct Selection {
+    pub drag: Option<Drag>,
+    // where a box selection started in world coordinates
+    pub box_start: Option<Vec2>
+}
+
+impl Selection {
+    pub fn new() -> Self {
+        Self {
+            drag: None,
+            box_start: None,
+        }
+    }
+}
+
+impl Default for Selection {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Editor {
+
+    pub fn selected_ids(&self) -> Vec<String> {
+        self.structure_ids().into_iter().filter(|id| self.level.structures[id].selected).collect()
+    }
+
+    pub fn set_selected(&mut self, ids: &[String]) {
+        for (id, structure) in self.level.structures.iter_mut() {
+            structure.selected = ids.contains(id);
+        }
+    }
+
+    // the structure drawn on top under the mouse, if any
+    pub fn structure_at(&mut self, position: &Vec2) -> Option<String> {
+
+        let intersections = self.level.space.query_point(position.clone());
+
+        // the space only knows about the box around each shape, so clicks in the corners of a circle go through to what is behind it
+        self.structure_ids().into_iter().rev().find(|id| {
+
+            let structure = &self.level.structures[id];
+
+            intersections.contains(&structure.rigid_body_handle) && structure.contains(&mut self.level.space, position)
+        })
+    }
+
+    // the average of the selected structures' centers, which group rotation and pasting go around
+    pub fn selection_center(&mut self, ids: &[String]) -> Vec2 {
+
+        let mut center = Vec2::new(0., 0.);
+
+        for id in ids {
+            let handle = self.level.structures[id].rigid_body_handle.clone();
+
+            let position = self.level.space.get_rigid_body_mut(&handle).unwrap().position.clone();
+
+            center.x += position.x / ids.len() as f32;
+            center.y += position.y / ids.len() as f32;
+        }
+
+        center
+    }
+
+    // menu_open is whether a structure menu was open before this frame's click was handled
+    pub fn update_selection(&mut self, menu_open: bool) {
+
+        let mouse_pos = self.mouse_world();
+
+        // a click while a menu is open is either for the menu or closes it, so it shouldnt change the selection too
+        if self.input.state.is_pressed(Action::SelectStructure) && menu_open {
+            for structure in self.level.structures.values_mut() {
+                structure.menu = None;
+            }
+        }
+
+        // resize handles get the click before selection does
+        let resizing = self.level.structures.values().any(|structure| structure.resize_anchor.is_some());
+
+        if self.input.state.is_pressed(Action::SelectStructure) && !menu_open && !resizing {
+
+            let adding = self.input.state.is_down(Action::AddToSelection);
+
+            let locked = self.locked_by_others();
+
+            match self.structure_at(&mouse_pos) {
+                Some(id) if locked.contains(&id) => {
+                    let holder = self.lock_holder(&id).unwrap_or("someone".to_string());
+
+                    self.set_status(format!("{} is editing that", holder));
+                },
+                Some(id) if adding => {
+                    let structure = self.level.structures.get_mut(&id).unwrap();
+
+                    structure.selected = !structure.selected;
+                },
+                Some(id) => {
+                    // clicking something already selected keeps the group so it can be dragged together
+                    if !self.level.structures[&id].selected {
+                        self.set_selected(&[id.clone()]);
+                    }
+
+                    self.start_drag(id, mouse_pos.clone());
+                },
+                None => {
+                    if !adding {
+                        self.set_selected(&[]);
+                    }
+
+                    self.selection.box_start = Some(mouse_pos.clone());
+                },
+            }
+        }
+
+        if self.selection.drag.is_some() {
+            self.drag_selected(&mouse_pos);
+        }
+
+        if self.input.state.is_released(Action::SelectStructure) {
+            self.finish_box_select(&mouse_pos);
+        }
+
+        self.rotate_selected();
+
+        if self.input.state.is_pressed(Action::DeleteSelected) {
+            for id in self.selected_ids() {
+                self.remove_structure(&id);
+            }
+        }
+
+        if self.input.state.is_pressed(Action::Duplicate) {
+            let clipboard_structures = self.copy_selected();
+
+            self.paste(clipboard_structures, Vec2::new(DUPLICATE_OFFSET, -DUPLICATE_OFFSET));
+        }
+
+        if self.input.state.is_pressed(Action::Copy) {
+            self.copy_to_clipboard();
+        }
+
+        if self.input.state.is_pressed(Action::Paste) {
+            self.paste_from_clipboard(&mouse_pos);
+        }
+
+        if self.input.state.is_pressed(Action::NumericEntry) {
+            self.open_numeric_entry();
+        }
+    }
+
+    fn start_drag(&mut self, anchor: String, mouse_start: Vec2) {
+
+        let mut start_positions = HashMap::new();
+
+        for id in self.selected_ids() {
+            let handle = self.level.structures[&id].rigid_body_handle.clone();
+
+            start_positions.insert(id, self.level.space.get_rigid_body_mut(&handle).unwrap().position.clone());
+        }
+
+        self.selection.drag = Some(Drag { mouse_start, anchor, start_positions });
+    }
+
+    fn drag_selected(&mut self, mouse_pos: &Vec2) {
+
+        if !self.input.state.is_down(Action::SelectStructure) {
+
+            if let Some(drag) = self.selection.drag.take() {
+                for id in drag.start_positions.keys() {
+                    if let Some(structure) = self.level.structures.get_mut(id) {
+                        structure.dragging = false;
+                    }
+                }
+            }
+
+            self.snapping.guides.clear();
+
+            return;
+        }
+
+        let drag = self.selection.drag.as_ref().unwrap();
+
+        let anchor = drag.anchor.clone();
+        let anchor_start = drag.start_positions[&anchor].clone();
+
+        let target = self.snapping.snap_point(&Vec2::new(
+            anchor_start.x + mouse_pos.x - drag.mouse_start.x,
+            anchor_start.y + mouse_pos.y - drag.mouse_start.y
+        ));
+
+        let target = self.align(&anchor, target);
+
+        // everything else keeps its place relative to the anchor
+        let (delta_x, delta_y) = (target.x - anchor_start.x, target.y - anchor_start.y);
+
+        for (id, start_position) in &self.selection.drag.as_ref().unwrap().start_positions {
+
+            let structure = match self.level.structures.get_mut(id) {
+                Some(structure) => structure,
+                None => continue,
+            };
+
+            structure.dragging = true;
+
+            let rigid_body = self.level.space.get_rigid_body_mut(&structure.rigid_body_handle).unwrap();
+
+            rigid_body.position = Vec2::new(start_position.x + delta_x, start_position.y + delta_y);
+            rigid_body.velocity = Vec2::ZERO;
+        }
+    }
+
+    fn finish_box_select(&mut self, mouse_pos: &Vec2) {
+
+        let start = match self.selection.box_start.take() {
+            Some(start) => start,
+            None => return,
+        };
+
+        let (min_x, max_x) = (start.x.min(mouse_pos.x), start.x.max(mouse_pos.x));
+        let (min_y, max_y) = (start.y.min(mouse_pos.y), start.y.max(mouse_pos.y));
+
+        let locked = self.locked_by_others();
+
+        for (id, structure) in self.level.structures.iter_mut() {
+
+            if locked.contains(id) {
+                continue;
+            }
+
+            let position = &self.level.space.get_rigid_body_mut(&structure.rigid_body_handle).unwrap().position;
+
+            if position.x >= min_x && position.x <= max_x && position.y >= min_y && position.y <= max_y {
+                structure.selected = true;
+            }
+        }
+    }
+
+    // the whole selection turns around its center rather than each structure spinning in place.
+    // with angle snapping each press turns to the next step instead of turning smoothly while held
+    fn rotate_selected(&mut self) {
+
+        let ids = self.selected_ids();
+
+        if ids.is_empty() {
+            return;
+        }
+
+        let delta = match self.snapping.angle {
+            true => {
+                if !self.input.state.is_pressed(Action::RotateSelected) {
+                    return;
+                }
+
+                let step = self.snapping.angle_step();
+
+                let handle = self.level.structures[&ids[0]].rigid_body_handle.clone();
+
+                let rotation = self.level.space.get_rigid_body_mut(&handle).unwrap().rotation;
+
+                step_below(rotation, step) - rotation
+            },
+            false => {
+                if !self.input.state.is_down(Action::RotateSelected) {
+                    return;
+                }
+
+                -ROTATION_SPEED
+            },
+        };
+
+        self.rotate_structures(&ids, delta);
+    }
+
+    pub fn rotate_structures(&mut self, ids: &[String], delta: f32) {
+
+        let pivot = self.selection_center(ids);
+
+        for id in ids {
+            let handle = self.level.structures[id].rigid_body_handle.clone();
+
+            let rigid_body = self.level.space.get_rigid_body_mut(&handle).unwrap();
+
+            let offset = rotate_vector(&Vec2::new(rigid_body.position.x - pivot.x, rigid_body.position.y - pivot.y), delta);
+
+            rigid_body.position = Vec2::new(pivot.x + offset.x, pivot.y + offset.y);
+            rigid_body.rotation += delta;
+        }
+    }
+
+    pub fn copy_selected(&mut self) -> Vec<ClipboardStructure> {
+
+        let mut clipboard_structures = vec![];
+
+        for id in self.selected_ids() {
+
+            let mut structure = self.level.structures[&id].clone();
+
+            structure.clear_editor_state();
+
+            let rigid_body = self.level.space.get_rigid_body_mut(structure.get_rigid_body_handle()).unwrap().clone();
+
+            clipboard_structures.push(ClipboardStructure { structure, rigid_body });
+        }
+
+        clipboard_structures
+    }
+
+    // inserts copies moved by offset and selects them instead of whatever was selected before
+    pub fn paste(&mut self, clipboard_structures: Vec<ClipboardStructure>, offset: Vec2) {
+
+        let mut pasted = vec![];
+
+        for ClipboardStructure { mut structure, mut rigid_body } in clipboard_structures {
+
+            rigid_body.position = Vec2::new(rigid_body.position.x + offset.x, rigid_body.position.y + offset.y);
+            rigid_body.owner = "host".to_string();
+            rigid_body.collider.owner = "host".to_string();
+
+            structure.rigid_body_handle = self.level.space.insert_rigid_body(rigid_body);
+            structure.owner = "host".to_string();
+
+            let id = gamelibrary::uuid();
+
+            self.level.structures.insert(id.clone(), structure);
+
+            pasted.push(id);
+        }
+
+        if !pasted.is_empty() {
+            self.set_selected(&pasted);
+        }
+    }
+
+    pub fn copy_to_clipboard(&mut self) {
+
+        let clipboard_structures = self.copy_selected();
+
+        if clipboard_structures.is_empty() {
+            return;
+        }
+
+        match serde_json::to_string(&clipboard_structures) {
+            Ok(json) => {
+                clipboard_set(&json);
+
+                self.set_status(format!("copied {} structures", clipboard_structures.len()));
+            },
+            Err(error) => warn!(%error, "failed to copy structures"),
+        }
+    }
+
+    // pasted structures keep their layout and are centered on the mouse
+    pub fn paste_from_clipboard(&mut self, mouse_pos: &Vec2) {
+
+        let json = match clipboard_get() {
+            Some(json) => json,
+            None => return,
+        };
+
+        let clipboard_structures: Vec<ClipboardStructure> = match serde_json::from_str(&json) {
+            Ok(clipboard_structures) => clipboard_structures,
+            Err(_) => {
+                self.set_status("the clipboard doesnt contain structures".to_string());
+
+                return;
+            },
+        };
+
+        let count = clipboard_structures.len();
+
+        if count == 0 {
+            return;
+        }
+
+        let (mut center_x, mut center_y) = (0., 0.);
+
+        for clipboard_structure in &clipboard_structures {
+            center_x += clipboard_structure.rigid_body.position.x / count as f32;
+            center_y += clipboard_structure.rigid_body.position.y / count as f32;
+        }
+
+        self.paste(clipboard_structures, Vec2::new(mouse_pos.x - center_x, mouse_pos.y - center_y));
+
+        self.set_status(format!("pasted {} structures", count));
+    }
+
+    pub fn draw_selection_box(&self) {
+
+        let start = match &self.selection.box_start {
+            Some(start) => start,
+            None => return,
+        };
+
+        let end = self.mouse_world();
+
+        let offset = self.camera.offset();
+
+        let (x, y) = (start.x.min(end.x) - offset.x, screen_height() - (start.y.max(end.y) - offset.y));
+        let (width, height) = ((end.x - start.x).abs(), (end.y - start.y).abs());
+
+        draw_rectangle(x, y, width, height, Color::new(1., 1., 1., 0.1));
+        draw_rectangle_lines(x, y, width, height, 1. / self.camera.zoom, WHITE);
+    }
+}
@@ -0,0 +1,355 @@
+use gamelibrary::{proxies::macroquad::math::vec2::Vec2, rigid_body::RigidBody};
+use liquidators_lib::input::Action;
+use macroquad::{color::{Color, SKYBLUE}, shapes::draw_line, window::screen_height};
+
+use crate::editor::Editor;
+
+// the sizes the grid steps through when it is made bigger or smaller
+pub const GRID_SIZES: [f32; 7] = [5., 10., 20., 40., 80., 160., 320.];
+pub const DEFAULT_GRID_SIZE: usize = 2;
+pub const DEFAULT_ANGLE_STEP_DEGREES: f32 = 15.;
+// how close in pixels an edge or center has to be to another structure's to line up with it
+pub const GUIDE_DISTANCE: f32 = 6.;
+// the grid isnt drawn if it would need more lines than this across the screen
+const MAX_GRID_LINES: f32 = 300.;
+
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub enum Guide {
+    // a line at this x
+    Vertical(f32),
+    // a line at this y
+    Horizontal(f32)
+}
+
+pub struct Snapping {
+    pub grid: bool,
+    // index into GRID_SIZES
+    pub grid_size: usize,
+    pub angle: bool,
+    pub angle_step_degrees: f32,
+    // the alignment guides the structure being dragged is currently lined up with
+    pub guides: Vec<Guide>
+}
+
+impl Snapping {
+    pub fn new() -> Self {
+        Self {
+            grid: false,
+            grid_size: DEFAULT_GRID_SIZE,
+            angle: false,
+            angle_step_degrees: DEFAULT_ANGLE_STEP_DEGREES,
+            guides: vec![],
+        }
+    }
+
+    // the grid spacing if snapping to it is turned on
+    pub fn grid_size(&self) -> Option<f32> {
+        match self.grid {
+            true => Some(GRID_SIZES[self.grid_size]),
+            false => None,
+        }
+    }
+
+    pub fn snap_point(&self, point: &Vec2) -> Vec2 {
+        match self.grid_size() {
+            Some(size) => Vec2::new((point.x / size).round() * size, (point.y / size).round() * size),
+            None => point.clone(),
+        }
+    }
+
+    pub fn angle_step(&self) -> f32 {
+        self.angle_step_degrees.to_radians()
+    }
+}
+
+impl Default for Snapping {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+// the axis aligned box around a possibly rotated rigid body, as (min x, min y, max x, max y)
+pub fn bounding_box(rigid_body: &RigidBody, position: &Vec2) -> (f32, f32, f32, f32) {
+
+    let (sin, cos) = rigid_body.rotation.sin_cos();
+
+    let extent_x = (rigid_body.collider.hx * cos).abs() + (rigid_body.collider.hy * sin).abs();
+    let extent_y = (rigid_body.collider.hx * sin).abs() + (rigid_body.collider.hy * cos).abs();
+
+    (position.x - extent_x, position.y - extent_y, position.x + extent_x, position.y + extent_y)
+}
+
+// the angle step below the given rotation, so an unsnapped rotation snaps onto the steps first.
+// the small nudge stops a rotation thats only off a step by float error from counting as past it
+pub fn step_below(rotation: f32, step: f32) -> f32 {
+    ((rotation / step - 0.001).ceil() - 1.) * step
+}
+
+fn is_closer(best: Option<(f32, f32)>, distance: f32) -> bool {
+    match best {
+        Some((best, _)) => distance.abs() < best.abs(),
+        None => true,
+    }
+}
+
+impl Editor {
+
+    pub fn update_snapping(&mut self) {
+
+        if self.input.state.is_pressed(Action::ToggleGrid) {
+            self.snapping.grid = !self.snapping.grid;
+        }
+
+        if self.input.state.is_pressed(Action::GrowGrid) {
+            self.snapping.grid_size = (self.snapping.grid_size + 1).min(GRID_SIZES.len() - 1);
+        }
+
+        if self.input.state.is_pressed(Action::ShrinkGrid) {
+            self.snapping.grid_size = self.snapping.grid_size.saturating_sub(1);
+        }
+
+        if self.input.state.is_pressed(Action::ToggleAngleSnap) {
+            self.snapping.angle = !self.snapping.angle;
+        }
+    }
+
+    // moves a structure that would end up at position so its edges or center line up with nearby unselected structures,
+    // and remembers which guides it lined up with so they can be drawn
+    pub fn align(&mut self, id: &String, position: Vec2) -> Vec2 {
+
+        self.snapping.guides.clear();
+
+        let handle = self.level.structures[id].rigid_body_handle.clone();
+
+        let (min_x, min_y, max_x, max_y) = bounding_box(self.level.space.get_rigid_body_mut(&handle).unwrap(), &position);
+
+        // measured on screen so lining up feels the same at any zoom
+        let guide_distance = GUIDE_DISTANCE / self.camera.zoom;
+
+        let moving_x = [min_x, position.x, max_x];
+        let moving_y = [min_y, position.y, max_y];
+
+        // the closest line up on each axis as (distance to move, where the guide goes)
+        let mut best_x: Option<(f32, f32)> = None;
+        let mut best_y: Option<(f32, f32)> = None;
+
+        for other_id in self.structure_ids() {
+
+            let other = &self.level.structures[&other_id];
+
+            if other.selected {
+                continue;
+            }
+
+            let other_body = self.level.space.get_rigid_body_mut(&other.rigid_body_handle).unwrap();
+
+            let other_position = other_body.position.clone();
+
+            let (other_min_x, other_min_y, other_max_x, other_max_y) = bounding_box(other_body, &other_position);
+
+            for target in [other_min_x, other_position.x, other_max_x] {
+                for moving in moving_x {
+                    let distance = target - moving;
+
+                    if distance.abs() < guide_distance && is_closer(best_x, distance) {
+                        best_x = Some((distance, target));
+                    }
+                }
+            }
+
+            for target in [other_min_y, other_position.y, other_max_y] {
+                for moving in moving_y {
+                    let distance = target - moving;
+
+                    if distance.abs() < guide_distance && is_closer(best_y, distance) {
+                        best_y = Some((distance, target));
+                    }
+                }
+            }
+        }
+
+        let mut aligned = position;
+
+        if let Some((distance, x)) = best_x {
+            aligned.x += distance;
+
+            self.snapping.guides.push(Guide::Vertical(x));
+        }
+
+        if let Some((distance, y)) = best_y {
+            aligned.y += distance;
+
+            self.snapping.guides.push(Guide::Horizontal(y));
+        }
+
+        aligned
+    }
+
+    // drawn with the level's camera, only over the part of the level that is on screen
+    pub fn draw_grid(&self) {
+
+        let size = match self.snapping.grid_size() {
+            Some(size) => size,
+            None => return,
+        };
+
+        let (min, max) = self.camera.visible_world();
+
+        // zoomed far out a small grid would be thousands of lines that all blur together
+        if (max.x - min.x) / size > MAX_GRID_LINES || (max.y - min.y) / size > MAX_GRID_LINES {
+            return;
+        }
+
+        let offset = self.camera.offset();
+        let color = Color::new(1., 1., 1., 0.08);
+        let thickness = 1. / self.camera.zoom;
+
+        let (top, bottom) = (screen_height() - (max.y - offset.y), screen_height() - (min.y - offset.y));
+
+        let mut x = (min.x / size).floor() * size;
+
+        while x <= max.x {
+            draw_line(x - offset.x, top, x - offset.x, bottom, thickness, color);
+
+            x += size;
+        }
+
+        let mut y = (min.y / size).floor() * size;
+
+        while y <= max.y {
+            draw_line(min.x - offset.x, screen_height() - (y - offset.y), max.x - offset.x, screen_height() - (y - offset.y), thickness, color);
+
+            y += size;
+        }
+    }
+
+    pub fn draw_guides(&self) {
+
+        let (min, max) = self.camera.visible_world();
+
+        let offset = self.camera.offset();
+        let thickness = 1. / self.camera.zoom;
+
+        for guide in &self.snapping.guides {
+            match guide {
+                Guide::Vertical(x) => draw_line(
+                    x - offset.x,
+                    screen_height() - (max.y - offset.y),
+                    x - offset.x,
+                    screen_height() - (min.y - offset.y),
+                    thickness,
+                    SKYBLUE
+                ),
+                Guide::Horizontal(y) => draw_line(
+                    min.x - offset.x,
+                    screen_height() - (y - offset.y),
+                    max.x - offset.x,
+                    screen_height() - (y - offset.y),
+                    thickness,
+                    SKYBLUE
+                ),
+            }
+        }
+    }
+
+    pub fn snapping_status(&self) -> String {
+
+        let grid = match self.snapping.grid_size() {
+            Some(size) => format!("grid {}", size),
+            None => "grid off".to_string(),
+        };
+
+        let angle = match self.snapping.angle {
+            true => format!("angle snap {}°", self.snapping.angle_step_degrees),
+            false => "angle snap off".to_string(),
+        };
+
+        format!("{}, {}", grid, angle)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use gamelibrary::{collider::Collider, rigid_body::RigidBodyType};
+
+    use super::*;
+
+    fn assert_close(a: f32, b: f32) {
+        assert!((a - b).abs() < 0.001, "{} isnt {}", a, b);
+    }
+
+    fn body(hx: f32, hy: f32, rotation: f32) -> RigidBody {
+        RigidBody {
+            position: Vec2::ZERO,
+            rotation,
+            angular_velocity: 0.,
+            velocity: Vec2::ZERO,
+            body_type: RigidBodyType::Fixed,
+            owner: "host".to_string(),
+            collider: Collider {
+                hx,
+                hy,
+                restitution: 0.,
+                mass: 10.,
+                owner: "host".to_string()
+            }
+        }
+    }
+
+    #[test]
+    fn points_round_to_the_nearest_grid_line() {
+
+        let mut snapping = Snapping::new();
+
+        snapping.grid = true;
+
+        // DEFAULT_GRID_SIZE is 20
+        let snapped = snapping.snap_point(&Vec2::new(29., -31.));
+
+        assert_eq!((snapped.x, snapped.y), (20., -40.));
+
+        // halfway rounds away from zero
+        let snapped = snapping.snap_point(&Vec2::new(30., -10.));
+
+        assert_eq!((snapped.x, snapped.y), (40., -20.));
+    }
+
+    #[test]
+    fn points_dont_move_without_the_grid() {
+
+        let snapped = Snapping::new().snap_point(&Vec2::new(29., -31.));
+
+        assert_eq!((snapped.x, snapped.y), (29., -31.));
+    }
+
+    #[test]
+    fn rotation_steps_down_to_the_next_angle() {
+
+        let step = 15_f32.to_radians();
+
+        assert_close(step_below(0., step), -step);
+        assert_close(step_below(step * 3., step), step * 2.);
+
+        // unsnapped goes onto the step just below it rather than a whole step further
+        assert_close(step_below(20_f32.to_radians(), step), step);
+        assert_close(step_below(-20_f32.to_radians(), step), -step * 2.);
+
+        // a hair past a step still counts as on it
+        assert_close(step_below(step + 0.000001, step), 0.);
+    }
+
+    #[test]
+    fn bounding_box_turns_with_the_body() {
+
+        let (min_x, min_y, max_x, max_y) = bounding_box(&body(10., 5., 0.), &Vec2::new(100., 50.));
+
+        assert_eq!((min_x, min_y, max_x, max_y), (90., 45., 110., 55.));
+
+        let (min_x, min_y, max_x, max_y) = bounding_box(&body(10., 5., std::f32::consts::FRAC_PI_2), &Vec2::new(100., 50.));
+
+        assert_close(min_x, 95.);
+        assert_close(min_y, 40.);
+        assert_close(max_x, 105.);
+        assert_close(max_y, 60.);
+    }
+}
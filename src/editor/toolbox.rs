@@ -0,0 +1,320 @@
+use gamelibrary::{proxies::macroquad::math::vec2::Vec2, rigid_body::RigidBodyType};
+use liquidators_lib::{input::Action, structure::{convex_hull, Shape}};
+use macroquad::{color::{Color, WHITE, YELLOW}, shapes::{draw_line, draw_rectangle}, text::draw_text, window::{screen_height, screen_width}};
+
+use crate::editor::Editor;
+
+// rectangles smaller than this when the mouse is let go are treated as a misclick
+const MIN_SHAPE_SIZE: f32 = 4.;
+
+const TOOLBOX_WIDTH: f32 = 220.;
+const LINE_HEIGHT: f32 = 22.;
+// how close in pixels a click has to be to a polygon's first corner to finish it
+const CLOSE_POLYGON_DISTANCE: f32 = 10.;
+
+// circles, capsules and polygons come out dynamic. the inspector can make them fixed
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub enum Tool {
+    // pick, drag, rotate and resize existing structures
+    Select,
+    DynamicRectangle,
+    FixedRectangle,
+    Circle,
+    Capsule,
+    // click each corner then click the first one again
+    Polygon
+}
+
+impl Tool {
+    pub const ALL: [Tool; 6] = [Tool::Select, Tool::DynamicRectangle, Tool::FixedRectangle, Tool::Circle, Tool::Capsule, Tool::Polygon];
+
+    pub fn action(&self) -> Action {
+        match self {
+            Tool::Select => Action::SelectTool,
+            Tool::DynamicRectangle => Action::DynamicRectangleTool,
+            Tool::FixedRectangle => Action::FixedRectangleTool,
+            Tool::Circle => Action::CircleTool,
+            Tool::Capsule => Action::CapsuleTool,
+            Tool::Polygon => Action::PolygonTool,
+        }
+    }
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            Tool::Select => "Select",
+            Tool::DynamicRectangle => "Dynamic rectangle",
+            Tool::FixedRectangle => "Fixed rectangle",
+            Tool::Circle => "Circle",
+            Tool::Capsule => "Capsule",
+            Tool::Polygon => "Polygon",
+        }
+    }
+
+    pub fn body_type(&self) -> Option<RigidBodyType> {
+        match self {
+            Tool::Select => None,
+            Tool::FixedRectangle => Some(RigidBodyType::Fixed),
+            _ => Some(RigidBodyType::Dynamic),
+        }
+    }
+
+    // the shape dragged out by the tools that place a box, fitted to it
+    pub fn dragged_shape(&self) -> Option<Shape> {
+        match self {
+            Tool::DynamicRectangle | Tool::FixedRectangle => Some(Shape::Rectangle),
+            Tool::Circle => Some(Shape::Circle),
+            Tool::Capsule => Some(Shape::Capsule),
+            _ => None,
+        }
+    }
+}
+
+pub struct Toolbox {
+    pub tool: Tool,
+    // where the mouse was pressed in world coordinates while a shape is being dragged out
+    pub shape_start: Option<Vec2>,
+    // the corners clicked so far with the polygon tool, in world coordinates
+    pub polygon_points: Vec<Vec2>
+}
+
+impl Toolbox {
+    pub fn new() -> Self {
+        Self {
+            tool: Tool::Select,
+            shape_start: None,
+            polygon_points: vec![],
+        }
+    }
+}
+
+// the structure for some clicked corners as (center, half extents, shape), or None if they dont make a convex shape with some area
+pub fn polygon_from_points(points: &[Vec2]) -> Option<(Vec2, f32, f32, Shape)> {
+
+    // whatever order the corners were clicked in, the structure gets the convex outline around them
+    let hull = convex_hull(points);
+
+    if hull.len() < 3 {
+        return None;
+    }
+
+    let min_x = hull.iter().map(|point| point.x).fold(f32::MAX, f32::min);
+    let max_x = hull.iter().map(|point| point.x).fold(f32::MIN, f32::max);
+    let min_y = hull.iter().map(|point| point.y).fold(f32::MAX, f32::min);
+    let max_y = hull.iter().map(|point| point.y).fold(f32::MIN, f32::max);
+
+    if max_x - min_x < MIN_SHAPE_SIZE || max_y - min_y < MIN_SHAPE_SIZE {
+        return None;
+    }
+
+    let center = Vec2::new((min_x + max_x) / 2., (min_y + max_y) / 2.);
+
+    let (hx, hy) = ((max_x - min_x) / 2., (max_y - min_y) / 2.);
+
+    let points = hull.iter().map(|point| Vec2::new((point.x - center.x) / hx, (point.y - center.y) / hy)).collect();
+
+    Some((center, hx, hy, Shape::Polygon(points)))
+}
+
+impl Default for Toolbox {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Editor {
+
+    pub fn update_toolbox(&mut self) {
+
+        for tool in Tool::ALL {
+            if self.input.state.is_pressed(tool.action()) {
+                self.toolbox.tool = tool;
+                self.toolbox.shape_start = None;
+                self.toolbox.polygon_points.clear();
+            }
+        }
+
+        let body_type = match self.toolbox.tool.body_type() {
+            Some(body_type) => body_type,
+            None => return,
+        };
+
+        let mouse_pos = self.snapping.snap_point(&self.mouse_world());
+
+        if self.toolbox.tool == Tool::Polygon {
+            self.update_polygon_tool(mouse_pos, body_type);
+
+            return;
+        }
+
+        if self.input.state.is_pressed(Action::PlaceShape) {
+            self.toolbox.shape_start = Some(mouse_pos.clone());
+        }
+
+        if !self.input.state.is_released(Action::PlaceShape) {
+            return;
+        }
+
+        let start = match self.toolbox.shape_start.take() {
+            Some(start) => start,
+            None => return,
+        };
+
+        let (width, height) = ((mouse_pos.x - start.x).abs(), (mouse_pos.y - start.y).abs());
+
+        if width < MIN_SHAPE_SIZE || height < MIN_SHAPE_SIZE {
+            return;
+        }
+
+        let shape = self.toolbox.tool.dragged_shape().unwrap_or_default();
+
+        let (mut hx, mut hy) = (width / 2., height / 2.);
+
+        // a circle fits inside what was dragged out, with a square box so the collider doesnt stick out past it
+        if shape == Shape::Circle {
+            hx = hx.min(hy);
+            hy = hx;
+        }
+
+        self.insert_structure(
+            Vec2::new((start.x + mouse_pos.x) / 2., (start.y + mouse_pos.y) / 2.),
+            hx,
+            hy,
+            body_type,
+            shape
+        );
+    }
+
+    fn update_polygon_tool(&mut self, mouse_pos: Vec2, body_type: RigidBodyType) {
+
+        if !self.input.state.is_pressed(Action::PlaceShape) {
+            return;
+        }
+
+        let close_distance = CLOSE_POLYGON_DISTANCE / self.camera.zoom;
+
+        let closing = self.toolbox.polygon_points.first().is_some_and(|first| {
+            (first.x - mouse_pos.x).abs() <= close_distance && (first.y - mouse_pos.y).abs() <= close_distance
+        });
+
+        if !closing {
+            self.toolbox.polygon_points.push(mouse_pos);
+
+            return;
+        }
+
+        let points = std::mem::take(&mut self.toolbox.polygon_points);
+
+        match polygon_from_points(&points) {
+            Some((center, hx, hy, shape)) => self.insert_structure(center, hx, hy, body_type, shape),
+            None => self.set_status("a polygon needs at least 3 corners that arent all in a line".to_string()),
+        }
+    }
+
+    pub fn draw_toolbox(&self) {
+
+        let x = screen_width() - TOOLBOX_WIDTH - 10.;
+
+        draw_rectangle(x, 10., TOOLBOX_WIDTH, LINE_HEIGHT * (Tool::ALL.len() + 1) as f32 + 10., Color::new(0., 0., 0., 0.6));
+
+        draw_text("Tools", x + 10., 10. + LINE_HEIGHT, 22., WHITE);
+
+        for (index, tool) in Tool::ALL.iter().enumerate() {
+
+            let color = match *tool == self.toolbox.tool {
+                true => YELLOW,
+                false => WHITE,
+            };
+
+            let bindings: Vec<String> = self.input.bindings.get(tool.action()).iter().map(|binding| binding.to_string()).collect();
+
+            draw_text(
+                &format!("{} ({})", tool.name(), bindings.join(", ")),
+                x + 10.,
+                10. + LINE_HEIGHT * (index + 2) as f32,
+                20.,
+                color
+            );
+        }
+    }
+
+    // outline of the shape being dragged out, or the polygon clicked so far. drawn with the level's camera
+    pub fn draw_shape_preview(&self) {
+
+        let end = self.snapping.snap_point(&self.mouse_world());
+
+        let points: Vec<Vec2> = match (&self.toolbox.shape_start, self.toolbox.tool.dragged_shape()) {
+            (Some(start), Some(shape)) => {
+
+                let center = Vec2::new((start.x + end.x) / 2., (start.y + end.y) / 2.);
+
+                let (mut hx, mut hy) = ((end.x - start.x).abs() / 2., (end.y - start.y).abs() / 2.);
+
+                if shape == Shape::Circle {
+                    hx = hx.min(hy);
+                    hy = hx;
+                }
+
+                let mut outline: Vec<Vec2> = shape.outline(hx, hy).iter().map(|point| Vec2::new(center.x + point.x, center.y + point.y)).collect();
+
+                // closes the outline
+                outline.push(outline[0].clone());
+
+                outline
+            },
+            _ if self.toolbox.tool == Tool::Polygon && !self.toolbox.polygon_points.is_empty() => {
+
+                let mut points = self.toolbox.polygon_points.clone();
+
+                points.push(end);
+
+                points
+            },
+            _ => return,
+        };
+
+        let offset = self.camera.offset();
+
+        for pair in points.windows(2) {
+            draw_line(
+                pair[0].x - offset.x,
+                screen_height() - (pair[0].y - offset.y),
+                pair[1].x - offset.x,
+                screen_height() - (pair[1].y - offset.y),
+                2. / self.camera.zoom,
+                WHITE
+            );
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn polygon_points_are_fitted_to_their_box() {
+
+        // clicked out of order, with one corner inside the others
+        let points = [Vec2::new(100., 0.), Vec2::new(0., 0.), Vec2::new(50., 10.), Vec2::new(0., 50.)];
+
+        let (center, hx, hy, shape) = polygon_from_points(&points).unwrap();
+
+        assert_eq!((center.x, center.y, hx, hy), (50., 25., 50., 25.));
+
+        match shape {
+            Shape::Polygon(points) => {
+                let points: Vec<(f32, f32)> = points.iter().map(|point| (point.x, point.y)).collect();
+
+                assert_eq!(points, vec![(-1., -1.), (1., -1.), (-1., 1.)]);
+            },
+            _ => panic!("expected a polygon"),
+        }
+    }
+
+    #[test]
+    fn polygons_need_some_area() {
+
+        assert!(polygon_from_points(&[Vec2::new(0., 0.), Vec2::new(50., 0.)]).is_none());
+        assert!(polygon_from_points(&[Vec2::new(0., 0.), Vec2::new(50., 0.), Vec2::new(100., 0.)]).is_none());
+    }
+}
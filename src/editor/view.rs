@@ -0,0 +1,79 @@
+use gamelibrary::proxies::macroquad::math::vec2::Vec2;
+use liquidators_lib::input::Action;
+
+use crate::{editor::Editor, snapping::bounding_box};
+
+// world units left around the selection when framing it
+const FRAME_PADDING: f32 = 50.;
+
+impl Editor {
+
+    // where the mouse is in the level, taking the camera into account
+    pub fn mouse_world(&self) -> Vec2 {
+        self.camera.screen_to_world(&self.input.state.mouse_position)
+    }
+
+    // returns true while the camera is being dragged around, in which case the mouse shouldnt do anything else
+    pub fn update_view(&mut self) -> bool {
+
+        let mouse_position = self.input.state.mouse_position.clone();
+
+        self.camera.zoom_at(self.input.state.mouse_wheel, &mouse_position);
+
+        if self.input.state.is_pressed(Action::FrameSelection) {
+            self.frame_selection();
+        }
+
+        let panning = self.input.state.is_down(Action::PanCamera)
+            || (self.input.state.is_down(Action::PanModifier) && self.input.state.is_down(Action::SelectStructure));
+
+        if !panning {
+            self.pan_last = None;
+
+            // holding the modifier on its own shouldnt start a selection or a shape either
+            return self.input.state.is_down(Action::PanModifier);
+        }
+
+        if let Some(last) = self.pan_last.replace(mouse_position.clone()) {
+            // the level moves with the mouse, and screen y goes down while world y goes up
+            self.camera.pan(last.x - mouse_position.x, mouse_position.y - last.y);
+        }
+
+        true
+    }
+
+    // fits the selected structures on screen, or the whole level if nothing is selected
+    pub fn frame_selection(&mut self) {
+
+        let mut ids = self.selected_ids();
+
+        if ids.is_empty() {
+            ids = self.structure_ids();
+        }
+
+        if let Some((min, max)) = self.structures_area(ids) {
+            self.camera.frame(&min, &max, FRAME_PADDING);
+        }
+    }
+
+    // the box around all of the given structures, None if there arent any
+    pub fn structures_area(&mut self, ids: Vec<String>) -> Option<(Vec2, Vec2)> {
+
+        let mut area: Option<(Vec2, Vec2)> = None;
+
+        for id in ids {
+            let handle = self.level.structures[&id].rigid_body_handle.clone();
+
+            let rigid_body = self.level.space.get_rigid_body_mut(&handle).unwrap();
+
+            let (min_x, min_y, max_x, max_y) = bounding_box(rigid_body, &rigid_body.position.clone());
+
+            area = Some(match area {
+                Some((min, max)) => (Vec2::new(min.x.min(min_x), min.y.min(min_y)), Vec2::new(max.x.max(max_x), max.y.max(max_y))),
+                None => (Vec2::new(min_x, min_y), Vec2::new(max_x, max_y)),
+            });
+        }
+
+        area
+    }
+}
@@ -0,0 +1,104 @@
+use diff::Diff;
+use gamelibrary::{proxies::macroquad::math::vec2::Vec2, space::{RigidBodyHandle, Space}, traits::{HasOwner, HasRigidBody}};
+use serde::{Deserialize, Serialize};
+
+use std::collections::HashMap;
+
+use macroquad::texture::Texture2D;
+
+use crate::{physics_square::PhysicsSquare, player::Player, structure::Structure, TickContext};
+
+// every kind of thing that can exist in the game state. adding a new kind of entity only means adding a variant here
+#[derive(Serialize, Deserialize, Diff, PartialEq, Clone)]
+#[diff(attr(
+    #[derive(Serialize, Deserialize)]
+))]
+pub enum Entity {
+    PhysicsSquare(PhysicsSquare),
+    Player(Player),
+    // part of the level. these dont do anything by themselves, physics moves the dynamic ones
+    Structure(Structure)
+}
+
+impl Entity {
+
+    pub fn tick(&mut self, ctx: &mut TickContext) {
+        match self {
+            Entity::PhysicsSquare(physics_square) => physics_square.tick(ctx),
+            Entity::Player(player) => player.tick(ctx),
+            Entity::Structure(structure) => structure.tick(ctx),
+        }
+    }
+
+    pub async fn draw(&mut self, camera_offset: &Vec2, space: &mut Space, textures: &HashMap<String, Texture2D>) {
+        match self {
+            Entity::PhysicsSquare(physics_square) => physics_square.draw(camera_offset, space).await,
+            Entity::Player(player) => player.draw(camera_offset, space, textures).await,
+            Entity::Structure(structure) => structure.draw_textured(camera_offset, space, textures).await,
+        }
+    }
+
+    pub fn rigid_body_handle(&self) -> &RigidBodyHandle {
+        match self {
+            Entity::PhysicsSquare(physics_square) => physics_square.get_rigid_body_handle(),
+            Entity::Player(player) => player.get_rigid_body_handle(),
+            Entity::Structure(structure) => structure.get_rigid_body_handle(),
+        }
+    }
+
+    // whether the owner is directly controlling this entity
+    pub fn controllable(&self) -> bool {
+        match self {
+            Entity::PhysicsSquare(physics_square) => physics_square.controllable,
+            Entity::Player(_) => true,
+            Entity::Structure(_) => false,
+        }
+    }
+}
+
+impl HasOwner for Entity {
+    fn get_owner(&self) -> String {
+        match self {
+            Entity::PhysicsSquare(physics_square) => physics_square.get_owner(),
+            Entity::Player(player) => player.get_owner(),
+            Entity::Structure(structure) => structure.get_owner(),
+        }
+    }
+
+    fn set_owner(&mut self, uuid: String) {
+        match self {
+            Entity::PhysicsSquare(physics_square) => physics_square.set_owner(uuid),
+            Entity::Player(player) => player.set_owner(uuid),
+            Entity::Structure(structure) => structure.set_owner(uuid),
+        }
+    }
+}
+
+// tick every entity the local player owns
+pub fn tick_entities(ctx: &mut TickContext) {
+
+    let ids: Vec<String> = ctx.game_state.entities.keys().cloned().collect();
+
+    for id in ids {
+
+        // take the entity out, tick it, then put it back in under the same id
+        let mut entity = match ctx.game_state.entities.remove(&id) {
+            Some(entity) => entity,
+            None => continue, // something else removed it this tick
+        };
+
+        // we only tick the entity if we own it
+        if entity.get_owner() == *ctx.uuid {
+            entity.tick(ctx);
+
+            // players set their own speed every tick so friction would only slow them down
+            if !matches!(entity, Entity::Player(_)) {
+                ctx.game_state.apply_friction(entity.rigid_body_handle(), ctx.time.elapsed().num_milliseconds() as f32 / 1000.);
+            }
+
+            ctx.game_state.enforce_bounds(entity.rigid_body_handle());
+        }
+
+        ctx.game_state.entities.insert(id, entity);
+    }
+}
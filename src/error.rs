@@ -0,0 +1,143 @@
+use std::fmt::Display;
+
+use lz4_flex::block::DecompressError;
+
+// something was wrong with the bytes of a packet we received
+#[derive(Debug)]
+pub enum CodecError {
+    Decompress(DecompressError),
+    Deserialize(bitcode::Error)
+}
+
+impl Display for CodecError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            CodecError::Decompress(error) => write!(f, "failed to decompress packet: {}", error),
+            CodecError::Deserialize(error) => write!(f, "failed to deserialize packet: {}", error),
+        }
+    }
+}
+
+impl std::error::Error for CodecError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            // lz4_flex only implements Error for this with its std feature, which we turn off
+            CodecError::Decompress(_) => None,
+            CodecError::Deserialize(error) => Some(error),
+        }
+    }
+}
+
+impl From<DecompressError> for CodecError {
+    fn from(error: DecompressError) -> Self {
+        CodecError::Decompress(error)
+    }
+}
+
+impl From<bitcode::Error> for CodecError {
+    fn from(error: bitcode::Error) -> Self {
+        CodecError::Deserialize(error)
+    }
+}
+
+#[derive(Debug)]
+pub enum NetError {
+    // we couldnt open a connection to the server at all
+    Connect(String),
+    Handshake(tungstenite::Error),
+    Io(std::io::Error),
+    WebSocket(tungstenite::Error),
+    // the client websocket only gives us error messages as strings
+    Socket(String),
+    Closed,
+    // the other side sent something that doesnt make sense at this point, like a text message
+    UnexpectedMessage(String),
+    Timeout,
+    Codec(CodecError)
+}
+
+impl Display for NetError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            NetError::Connect(error) => write!(f, "failed to connect: {}", error),
+            NetError::Handshake(error) => write!(f, "websocket handshake failed: {}", error),
+            NetError::Io(error) => write!(f, "socket io error: {}", error),
+            NetError::WebSocket(error) => write!(f, "websocket error: {}", error),
+            NetError::Socket(error) => write!(f, "websocket error: {}", error),
+            NetError::Closed => write!(f, "connection closed"),
+            NetError::UnexpectedMessage(message) => write!(f, "unexpected message: {}", message),
+            NetError::Timeout => write!(f, "timed out"),
+            NetError::Codec(error) => write!(f, "{}", error),
+        }
+    }
+}
+
+impl std::error::Error for NetError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            NetError::Handshake(error) => Some(error),
+            NetError::Io(error) => Some(error),
+            NetError::WebSocket(error) => Some(error),
+            NetError::Codec(error) => Some(error),
+            _ => None,
+        }
+    }
+}
+
+impl From<std::io::Error> for NetError {
+    fn from(error: std::io::Error) -> Self {
+        NetError::Io(error)
+    }
+}
+
+impl From<tungstenite::Error> for NetError {
+    fn from(error: tungstenite::Error) -> Self {
+        match error {
+            tungstenite::Error::ConnectionClosed | tungstenite::Error::AlreadyClosed => NetError::Closed,
+            tungstenite::Error::Io(io_error) => NetError::Io(io_error),
+            _ => NetError::WebSocket(error),
+        }
+    }
+}
+
+impl From<CodecError> for NetError {
+    fn from(error: CodecError) -> Self {
+        NetError::Codec(error)
+    }
+}
+
+#[derive(Debug)]
+pub enum LevelError {
+    Io(std::io::Error),
+    Parse(serde_json::Error)
+}
+
+impl Display for LevelError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            LevelError::Io(error) => write!(f, "failed to access level file: {}", error),
+            LevelError::Parse(error) => write!(f, "invalid level file: {}", error),
+        }
+    }
+}
+
+impl std::error::Error for LevelError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            LevelError::Io(error) => Some(error),
+            LevelError::Parse(error) => Some(error),
+        }
+    }
+}
+
+impl From<std::io::Error> for LevelError {
+    fn from(error: std::io::Error) -> Self {
+        LevelError::Io(error)
+    }
+}
+
+impl From<serde_json::Error> for LevelError {
+    fn from(error: serde_json::Error) -> Self {
+        LevelError::Parse(error)
+    }
+}
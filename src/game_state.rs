@@ -1,25 +1,128 @@
-use gamelibrary::space::Space;
+use std::collections::HashMap;
+
+use gamelibrary::{proxies::macroquad::math::vec2::Vec2, space::{RigidBodyHandle, Space}, traits::HasOwner};
 use diff::Diff;
+use macroquad::texture::Texture2D;
 use serde::{Deserialize, Serialize};
 
-use crate::physics_square::PhysicsSquare;
+use crate::{entities::Entity, level::Level, structure::apply_friction, world_bounds::WorldBounds};
 
 #[derive(Serialize, Deserialize, Diff, Clone, PartialEq)]
 #[diff(attr(
     #[derive(Serialize, Deserialize)]
 ))]
 pub struct GameState {
-    pub physics_squares: Vec<PhysicsSquare>,
-    pub space: Space
+    // keyed by a uuid so diffs refer to entities by id instead of by their position in a list
+    pub entities: HashMap<String, Entity>,
+    pub space: Space,
+    pub bounds: WorldBounds
 }
 
 impl GameState {
 
     pub fn empty() -> Self {
+        Self::with_gravity(0.)
+    }
+
+    pub fn with_gravity(gravity: f32) -> Self {
         Self {
-            physics_squares: vec![],
-            space: Space::new(0.)
+            entities: HashMap::new(),
+            space: Space::new(gravity),
+            bounds: WorldBounds::default()
+        }
+    }
+
+    // the level's structures become entities under the same ids, and the level's space comes along with its gravity
+    // everything in the level is handed to the given owner, who is then responsible for simulating it
+    pub fn from_level(level: &Level, owner: &String) -> Self {
+
+        let mut game_state = Self {
+            entities: HashMap::new(),
+            space: level.space.clone(),
+            bounds: level.bounds.clone()
+        };
+
+        for (id, structure) in &level.structures {
+
+            let mut structure = structure.clone();
+
+            // editor only state shouldnt leak into the game
+            structure.clear_editor_state();
+            structure.owner = owner.clone();
+
+            if let Some(rigid_body) = game_state.space.get_rigid_body_mut(&structure.rigid_body_handle) {
+                rigid_body.owner = owner.clone();
+                rigid_body.collider.owner = owner.clone();
+            }
+
+            game_state.entities.insert(id.clone(), Entity::Structure(structure));
+        }
+
+        game_state
+    }
+
+    // returns the id of the new entity
+    pub fn spawn(&mut self, entity: Entity) -> String {
+        let id = gamelibrary::uuid();
+
+        self.entities.insert(id.clone(), entity);
+
+        id
+    }
+
+    // keeps a body inside the world. only the owner of a body should do this, the same as ticking it
+    pub fn enforce_bounds(&mut self, rigid_body_handle: &RigidBodyHandle) {
+
+        if let Some(rigid_body) = self.space.get_rigid_body_mut(rigid_body_handle) {
+            self.bounds.enforce(rigid_body);
         }
     }
 
-}
\ No newline at end of file
+    // slows a body we own down if it is sliding across a structure with friction
+    pub fn apply_friction(&mut self, rigid_body_handle: &RigidBodyHandle, delta: f32) {
+
+        let structures = self.entities.values().filter_map(|entity| match entity {
+            Entity::Structure(structure) => Some(structure),
+            _ => None,
+        });
+
+        apply_friction(&mut self.space, rigid_body_handle, structures, delta);
+    }
+
+    // for owners that dont tick their entities, like the server with the level's structures.
+    // does what the space doesnt after a step: moves kinematic structures, then friction and the world bounds
+    pub fn settle_owned(&mut self, owner: &String, delta: f32) {
+
+        let ids: Vec<String> = self.entities.iter()
+            .filter(|(_, entity)| entity.get_owner() == *owner)
+            .map(|(id, _)| id.clone())
+            .collect();
+
+        for id in ids {
+
+            let handle = self.entities[&id].rigid_body_handle().clone();
+
+            if let Some(Entity::Structure(structure)) = self.entities.get(&id) {
+                structure.move_kinematic(&mut self.space, delta);
+            }
+
+            self.apply_friction(&handle, delta);
+
+            self.enforce_bounds(&handle);
+        }
+    }
+
+    pub async fn draw(&mut self, camera_offset: &Vec2, textures: &HashMap<String, Texture2D>) {
+
+        // hashmaps dont have a stable order so we sort by id to stop entities flickering over each other
+        let mut ids: Vec<String> = self.entities.keys().cloned().collect();
+        ids.sort();
+
+        for id in ids {
+            let entity = self.entities.get_mut(&id).unwrap();
+
+            entity.draw(camera_offset, &mut self.space, textures).await;
+        }
+    }
+
+}
@@ -0,0 +1,764 @@
+use std::collections::{HashMap, HashSet, VecDeque};
+
+use gamelibrary::proxies::macroquad::math::vec2::Vec2;
+use gilrs::{Axis, Button, EventType, Gilrs};
+use macroquad::input::{get_last_key_pressed, is_key_down, is_mouse_button_down, is_mouse_button_pressed, mouse_position, mouse_wheel, KeyCode, MouseButton};
+use serde::{Deserialize, Serialize};
+use tracing::{info, warn};
+
+pub const DEFAULT_BINDINGS_PATH: &str = "bindings.json";
+
+// how far a stick has to be pushed before it counts as pressed
+const AXIS_THRESHOLD: f32 = 0.5;
+
+// everything the player can do. game code asks about these instead of specific keys so they can be rebound
+#[derive(Serialize, Deserialize, Clone, Copy, PartialEq, Eq, Hash, Debug)]
+pub enum Action {
+    MoveUp,
+    MoveDown,
+    MoveLeft,
+    MoveRight,
+    Jump,
+    SpawnSquare,
+    SaveState,
+    LoadState,
+    DumpState,
+    CameraUp,
+    CameraDown,
+    CameraLeft,
+    CameraRight,
+    FollowNext,
+    FollowPrevious,
+    PlayerList,
+    Settings,
+    RotateSelected,
+    OpenStructureMenu,
+    SpawnDynamicStructure,
+    SpawnFixedStructure,
+    StepPhysics,
+    SaveLevel,
+    SaveLevelAs,
+    OpenLevel,
+    Undo,
+    Redo,
+    ResizeSelected,
+    SelectTool,
+    DynamicRectangleTool,
+    FixedRectangleTool,
+    CircleTool,
+    CapsuleTool,
+    PolygonTool,
+    PlaceShape,
+    SelectStructure,
+    AddToSelection,
+    DeleteSelected,
+    Duplicate,
+    Copy,
+    Paste,
+    ToggleGrid,
+    GrowGrid,
+    ShrinkGrid,
+    ToggleAngleSnap,
+    NumericEntry,
+    PanCamera,
+    PanModifier,
+    FrameSelection,
+    FitBounds,
+    PlayTest
+}
+
+impl Action {
+    pub const ALL: [Action; 51] = [
+        Action::MoveUp,
+        Action::MoveDown,
+        Action::MoveLeft,
+        Action::MoveRight,
+        Action::Jump,
+        Action::SpawnSquare,
+        Action::SaveState,
+        Action::LoadState,
+        Action::DumpState,
+        Action::CameraUp,
+        Action::CameraDown,
+        Action::CameraLeft,
+        Action::CameraRight,
+        Action::FollowNext,
+        Action::FollowPrevious,
+        Action::PlayerList,
+        Action::Settings,
+        Action::RotateSelected,
+        Action::OpenStructureMenu,
+        Action::SpawnDynamicStructure,
+        Action::SpawnFixedStructure,
+        Action::StepPhysics,
+        Action::SaveLevel,
+        Action::SaveLevelAs,
+        Action::OpenLevel,
+        Action::Undo,
+        Action::Redo,
+        Action::ResizeSelected,
+        Action::SelectTool,
+        Action::DynamicRectangleTool,
+        Action::FixedRectangleTool,
+        Action::CircleTool,
+        Action::CapsuleTool,
+        Action::PolygonTool,
+        Action::PlaceShape,
+        Action::SelectStructure,
+        Action::AddToSelection,
+        Action::DeleteSelected,
+        Action::Duplicate,
+        Action::Copy,
+        Action::Paste,
+        Action::ToggleGrid,
+        Action::GrowGrid,
+        Action::ShrinkGrid,
+        Action::ToggleAngleSnap,
+        Action::NumericEntry,
+        Action::PanCamera,
+        Action::PanModifier,
+        Action::FrameSelection,
+        Action::FitBounds,
+        Action::PlayTest,
+    ];
+
+    // the actions the game uses, in the order they are listed in the settings menu.
+    // everything else in ALL is only used by the editor so there is no point rebinding it from the game
+    pub const GAME: [Action; 17] = [
+        Action::MoveUp,
+        Action::MoveDown,
+        Action::MoveLeft,
+        Action::MoveRight,
+        Action::Jump,
+        Action::SpawnSquare,
+        Action::SaveState,
+        Action::LoadState,
+        Action::DumpState,
+        Action::CameraUp,
+        Action::CameraDown,
+        Action::CameraLeft,
+        Action::CameraRight,
+        Action::FollowNext,
+        Action::FollowPrevious,
+        Action::PlayerList,
+        Action::Settings,
+    ];
+}
+
+// one physical input. names are the same as the macroquad and gilrs enum variants, like "Space", "Left" or "South"
+#[derive(Serialize, Deserialize, Clone, PartialEq, Debug)]
+pub enum Binding {
+    Key(String),
+    // a key that only counts while modifiers like LeftControl are held
+    Combo {
+        modifiers: Vec<String>,
+        key: String
+    },
+    Mouse(String),
+    GamepadButton(String),
+    // positive is right or up on the stick
+    GamepadAxis {
+        axis: String,
+        positive: bool
+    }
+}
+
+impl Binding {
+
+    pub fn key(key: KeyCode) -> Self {
+        Binding::Key(format!("{:?}", key))
+    }
+
+    pub fn combo(modifiers: &[KeyCode], key: KeyCode) -> Self {
+        Binding::Combo {
+            modifiers: modifiers.iter().map(|modifier| format!("{:?}", modifier)).collect(),
+            key: format!("{:?}", key)
+        }
+    }
+
+    pub fn mouse(button: MouseButton) -> Self {
+        Binding::Mouse(format!("{:?}", button))
+    }
+
+    pub fn gamepad_button(button: Button) -> Self {
+        Binding::GamepadButton(format!("{:?}", button))
+    }
+
+    pub fn gamepad_axis(axis: Axis, positive: bool) -> Self {
+        Binding::GamepadAxis { axis: format!("{:?}", axis), positive }
+    }
+
+    pub fn is_down(&self, gilrs: &Option<Gilrs>) -> bool {
+        match self {
+            Binding::Key(name) => parse_key(name).is_some_and(is_key_down),
+            Binding::Combo { modifiers, key } => {
+                modifiers.iter().all(|modifier| parse_key(modifier).is_some_and(is_key_down)) && parse_key(key).is_some_and(is_key_down)
+            },
+            Binding::Mouse(name) => parse_mouse_button(name).is_some_and(is_mouse_button_down),
+            Binding::GamepadButton(name) => {
+
+                let (gilrs, button) = match (gilrs, parse_gamepad_button(name)) {
+                    (Some(gilrs), Some(button)) => (gilrs, button),
+                    _ => return false,
+                };
+
+                gilrs.gamepads().any(|(_, gamepad)| gamepad.is_pressed(button))
+            },
+            Binding::GamepadAxis { axis, positive } => {
+
+                let (gilrs, axis) = match (gilrs, parse_gamepad_axis(axis)) {
+                    (Some(gilrs), Some(axis)) => (gilrs, axis),
+                    _ => return false,
+                };
+
+                gilrs.gamepads().any(|(_, gamepad)| {
+                    match positive {
+                        true => gamepad.value(axis) > AXIS_THRESHOLD,
+                        false => gamepad.value(axis) < -AXIS_THRESHOLD,
+                    }
+                })
+            },
+        }
+    }
+}
+
+impl std::fmt::Display for Binding {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Binding::Key(name) => write!(f, "{}", name),
+            Binding::Combo { modifiers, key } => write!(f, "{}+{}", modifiers.join("+"), key),
+            Binding::Mouse(name) => write!(f, "Mouse {}", name),
+            Binding::GamepadButton(name) => write!(f, "Pad {}", name),
+            Binding::GamepadAxis { axis, positive: true } => write!(f, "Pad {}+", axis),
+            Binding::GamepadAxis { axis, positive: false } => write!(f, "Pad {}-", axis),
+        }
+    }
+}
+
+// macroquad key codes dont implement serde so we match them up by name instead
+const KEYS: &[KeyCode] = &[
+    KeyCode::A, KeyCode::B, KeyCode::C, KeyCode::D, KeyCode::E, KeyCode::F, KeyCode::G, KeyCode::H, KeyCode::I,
+    KeyCode::J, KeyCode::K, KeyCode::L, KeyCode::M, KeyCode::N, KeyCode::O, KeyCode::P, KeyCode::Q, KeyCode::R,
+    KeyCode::S, KeyCode::T, KeyCode::U, KeyCode::V, KeyCode::W, KeyCode::X, KeyCode::Y, KeyCode::Z,
+    KeyCode::Key0, KeyCode::Key1, KeyCode::Key2, KeyCode::Key3, KeyCode::Key4,
+    KeyCode::Key5, KeyCode::Key6, KeyCode::Key7, KeyCode::Key8, KeyCode::Key9,
+    KeyCode::F1, KeyCode::F2, KeyCode::F3, KeyCode::F4, KeyCode::F5, KeyCode::F6,
+    KeyCode::F7, KeyCode::F8, KeyCode::F9, KeyCode::F10, KeyCode::F11, KeyCode::F12,
+    KeyCode::Up, KeyCode::Down, KeyCode::Left, KeyCode::Right,
+    KeyCode::Space, KeyCode::Enter, KeyCode::Escape, KeyCode::Tab, KeyCode::Backspace, KeyCode::Delete,
+    KeyCode::LeftShift, KeyCode::RightShift, KeyCode::LeftControl, KeyCode::RightControl, KeyCode::LeftAlt, KeyCode::RightAlt,
+    KeyCode::LeftBracket, KeyCode::RightBracket, KeyCode::Comma, KeyCode::Period, KeyCode::Slash, KeyCode::Semicolon,
+    KeyCode::Minus, KeyCode::Equal, KeyCode::Apostrophe, KeyCode::Backslash, KeyCode::GraveAccent,
+    KeyCode::Home, KeyCode::End, KeyCode::PageUp, KeyCode::PageDown, KeyCode::Insert,
+    KeyCode::Kp0, KeyCode::Kp1, KeyCode::Kp2, KeyCode::Kp3, KeyCode::Kp4,
+    KeyCode::Kp5, KeyCode::Kp6, KeyCode::Kp7, KeyCode::Kp8, KeyCode::Kp9,
+    KeyCode::KpEnter, KeyCode::KpAdd, KeyCode::KpSubtract, KeyCode::KpMultiply, KeyCode::KpDivide,
+];
+
+const MOUSE_BUTTONS: &[MouseButton] = &[MouseButton::Left, MouseButton::Right, MouseButton::Middle];
+
+const GAMEPAD_BUTTONS: &[Button] = &[
+    Button::South, Button::East, Button::North, Button::West, Button::C, Button::Z,
+    Button::LeftTrigger, Button::LeftTrigger2, Button::RightTrigger, Button::RightTrigger2,
+    Button::Select, Button::Start, Button::Mode, Button::LeftThumb, Button::RightThumb,
+    Button::DPadUp, Button::DPadDown, Button::DPadLeft, Button::DPadRight,
+];
+
+const GAMEPAD_AXES: &[Axis] = &[
+    Axis::LeftStickX, Axis::LeftStickY, Axis::LeftZ, Axis::RightStickX, Axis::RightStickY, Axis::RightZ, Axis::DPadX, Axis::DPadY,
+];
+
+pub fn parse_key(name: &str) -> Option<KeyCode> {
+    KEYS.iter().copied().find(|key| format!("{:?}", key) == name)
+}
+
+// only keys we can read back from the bindings file, anything else would be saved and never fire
+pub fn bindable_key(key: KeyCode) -> Option<Binding> {
+    parse_key(&format!("{:?}", key)).map(Binding::key)
+}
+
+pub fn parse_mouse_button(name: &str) -> Option<MouseButton> {
+    MOUSE_BUTTONS.iter().copied().find(|button| format!("{:?}", button) == name)
+}
+
+pub fn parse_gamepad_button(name: &str) -> Option<Button> {
+    GAMEPAD_BUTTONS.iter().copied().find(|button| format!("{:?}", button) == name)
+}
+
+pub fn parse_gamepad_axis(name: &str) -> Option<Axis> {
+    GAMEPAD_AXES.iter().copied().find(|axis| format!("{:?}", axis) == name)
+}
+
+#[derive(Serialize, Deserialize, Clone)]
+pub struct Bindings {
+    pub actions: HashMap<Action, Vec<Binding>>
+}
+
+impl Default for Bindings {
+    fn default() -> Self {
+
+        let mut actions = HashMap::new();
+
+        for action in Action::ALL {
+            actions.insert(action, Bindings::default_bindings(action));
+        }
+
+        Self {
+            actions
+        }
+    }
+}
+
+impl Bindings {
+
+    pub fn default_bindings(action: Action) -> Vec<Binding> {
+        match action {
+            Action::MoveUp => vec![Binding::key(KeyCode::W), Binding::gamepad_axis(Axis::LeftStickY, true)],
+            Action::MoveDown => vec![Binding::key(KeyCode::S), Binding::gamepad_axis(Axis::LeftStickY, false)],
+            Action::MoveLeft => vec![Binding::key(KeyCode::A), Binding::gamepad_axis(Axis::LeftStickX, false), Binding::gamepad_button(Button::DPadLeft)],
+            Action::MoveRight => vec![Binding::key(KeyCode::D), Binding::gamepad_axis(Axis::LeftStickX, true), Binding::gamepad_button(Button::DPadRight)],
+            Action::Jump => vec![Binding::key(KeyCode::Space), Binding::key(KeyCode::W), Binding::gamepad_button(Button::South)],
+            Action::SpawnSquare => vec![Binding::mouse(MouseButton::Left), Binding::gamepad_button(Button::West)],
+            Action::SaveState => vec![Binding::key(KeyCode::F5)],
+            Action::LoadState => vec![Binding::key(KeyCode::F6)],
+            Action::DumpState => vec![Binding::key(KeyCode::J)],
+            Action::CameraUp => vec![Binding::key(KeyCode::Up), Binding::gamepad_axis(Axis::RightStickY, true)],
+            Action::CameraDown => vec![Binding::key(KeyCode::Down), Binding::gamepad_axis(Axis::RightStickY, false)],
+            Action::CameraLeft => vec![Binding::key(KeyCode::Left), Binding::gamepad_axis(Axis::RightStickX, false)],
+            Action::CameraRight => vec![Binding::key(KeyCode::Right), Binding::gamepad_axis(Axis::RightStickX, true)],
+            Action::FollowNext => vec![Binding::key(KeyCode::RightBracket), Binding::gamepad_button(Button::RightTrigger)],
+            Action::FollowPrevious => vec![Binding::key(KeyCode::LeftBracket), Binding::gamepad_button(Button::LeftTrigger)],
+            Action::PlayerList => vec![Binding::key(KeyCode::Tab), Binding::gamepad_button(Button::Select)],
+            Action::Settings => vec![Binding::key(KeyCode::F1), Binding::gamepad_button(Button::Start)],
+            Action::RotateSelected => vec![Binding::key(KeyCode::R)],
+            Action::OpenStructureMenu => vec![Binding::mouse(MouseButton::Right)],
+            Action::SpawnDynamicStructure => vec![Binding::key(KeyCode::E)],
+            Action::SpawnFixedStructure => vec![Binding::key(KeyCode::Q)],
+            Action::StepPhysics => vec![Binding::key(KeyCode::F)],
+            Action::SaveLevel => vec![Binding::combo(&[KeyCode::LeftControl], KeyCode::S), Binding::combo(&[KeyCode::RightControl], KeyCode::S)],
+            Action::SaveLevelAs => vec![Binding::combo(&[KeyCode::LeftControl, KeyCode::LeftShift], KeyCode::S), Binding::combo(&[KeyCode::RightControl, KeyCode::RightShift], KeyCode::S)],
+            Action::OpenLevel => vec![Binding::combo(&[KeyCode::LeftControl], KeyCode::O), Binding::combo(&[KeyCode::RightControl], KeyCode::O)],
+            Action::Undo => vec![Binding::combo(&[KeyCode::LeftControl], KeyCode::Z), Binding::combo(&[KeyCode::RightControl], KeyCode::Z)],
+            Action::Redo => vec![
+                Binding::combo(&[KeyCode::LeftControl], KeyCode::Y),
+                Binding::combo(&[KeyCode::RightControl], KeyCode::Y),
+                Binding::combo(&[KeyCode::LeftControl, KeyCode::LeftShift], KeyCode::Z),
+                Binding::combo(&[KeyCode::RightControl, KeyCode::RightShift], KeyCode::Z)
+            ],
+            Action::ResizeSelected => vec![Binding::mouse(MouseButton::Left)],
+            Action::SelectTool => vec![Binding::key(KeyCode::Key1)],
+            Action::DynamicRectangleTool => vec![Binding::key(KeyCode::Key2)],
+            Action::FixedRectangleTool => vec![Binding::key(KeyCode::Key3)],
+            Action::CircleTool => vec![Binding::key(KeyCode::Key4)],
+            Action::CapsuleTool => vec![Binding::key(KeyCode::Key5)],
+            Action::PolygonTool => vec![Binding::key(KeyCode::Key6)],
+            Action::PlaceShape => vec![Binding::mouse(MouseButton::Left)],
+            Action::SelectStructure => vec![Binding::mouse(MouseButton::Left)],
+            Action::AddToSelection => vec![Binding::key(KeyCode::LeftShift), Binding::key(KeyCode::RightShift)],
+            Action::DeleteSelected => vec![Binding::key(KeyCode::Delete)],
+            Action::Duplicate => vec![Binding::combo(&[KeyCode::LeftControl], KeyCode::D), Binding::combo(&[KeyCode::RightControl], KeyCode::D)],
+            Action::Copy => vec![Binding::combo(&[KeyCode::LeftControl], KeyCode::C), Binding::combo(&[KeyCode::RightControl], KeyCode::C)],
+            Action::Paste => vec![Binding::combo(&[KeyCode::LeftControl], KeyCode::V), Binding::combo(&[KeyCode::RightControl], KeyCode::V)],
+            Action::ToggleGrid => vec![Binding::key(KeyCode::G)],
+            Action::GrowGrid => vec![Binding::key(KeyCode::Equal)],
+            Action::ShrinkGrid => vec![Binding::key(KeyCode::Minus)],
+            Action::ToggleAngleSnap => vec![Binding::key(KeyCode::H)],
+            Action::NumericEntry => vec![Binding::key(KeyCode::N)],
+            Action::PanCamera => vec![Binding::mouse(MouseButton::Middle)],
+            // held with the left mouse button to pan for anyone without a middle button
+            Action::PanModifier => vec![Binding::key(KeyCode::Space)],
+            Action::FrameSelection => vec![Binding::key(KeyCode::Period)],
+            Action::FitBounds => vec![Binding::key(KeyCode::B)],
+            Action::PlayTest => vec![Binding::key(KeyCode::P)],
+        }
+    }
+
+    // anything missing from the file keeps its default so old binding files still work after new actions are added
+    pub fn load(path: &str) -> Self {
+
+        let mut bindings = Bindings::default();
+
+        let json = match std::fs::read_to_string(path) {
+            Ok(json) => json,
+            Err(_) => return bindings, // nothing has been rebound yet
+        };
+
+        match serde_json::from_str::<Bindings>(&json) {
+            Ok(loaded) => {
+                info!(%path, "loaded key bindings");
+
+                bindings.actions.extend(loaded.actions);
+            },
+            Err(error) => warn!(%path, %error, "failed to parse key bindings, using the defaults"),
+        }
+
+        bindings
+    }
+
+    pub fn save(&self, path: &str) {
+
+        let json = serde_json::to_string_pretty(self).unwrap();
+
+        if let Err(error) = std::fs::write(path, json) {
+            warn!(%path, %error, "failed to save key bindings");
+        }
+    }
+
+    pub fn get(&self, action: Action) -> &[Binding] {
+        match self.actions.get(&action) {
+            Some(bindings) => bindings,
+            None => &[],
+        }
+    }
+
+    // swaps out one of the action's bindings and leaves the rest alone. a slot past the end adds a new binding
+    pub fn replace(&mut self, action: Action, slot: usize, binding: Binding) {
+
+        let bindings = self.actions.entry(action).or_default();
+
+        match bindings.get_mut(slot) {
+            Some(existing) => *existing = binding,
+            None => bindings.push(binding),
+        }
+    }
+}
+
+// what the input looked like on one frame. entities only ever see this, so tests can build one by hand
+#[derive(Clone)]
+pub struct InputState {
+    pub down: HashSet<Action>,
+    // went down this frame
+    pub pressed: HashSet<Action>,
+    // went up this frame
+    pub released: HashSet<Action>,
+    // in screen coordinates
+    pub mouse_position: Vec2,
+    pub mouse_wheel: f32
+}
+
+impl Default for InputState {
+    fn default() -> Self {
+        Self {
+            down: HashSet::new(),
+            pressed: HashSet::new(),
+            released: HashSet::new(),
+            mouse_position: Vec2::new(0., 0.),
+            mouse_wheel: 0.,
+        }
+    }
+}
+
+impl InputState {
+
+    // works out what was pressed and released by comparing with the last frame
+    pub fn from_down(down: HashSet<Action>, previous: &InputState, mouse_position: Vec2, mouse_wheel: f32) -> Self {
+        Self {
+            pressed: down.difference(&previous.down).cloned().collect(),
+            released: previous.down.difference(&down).cloned().collect(),
+            down,
+            mouse_position,
+            mouse_wheel,
+        }
+    }
+
+    pub fn is_down(&self, action: Action) -> bool {
+        self.down.contains(&action)
+    }
+
+    pub fn is_pressed(&self, action: Action) -> bool {
+        self.pressed.contains(&action)
+    }
+
+    pub fn is_released(&self, action: Action) -> bool {
+        self.released.contains(&action)
+    }
+}
+
+// one frame of a script. actions listed here are held down for that frame
+#[derive(Serialize, Deserialize, Clone)]
+pub struct ScriptFrame {
+    pub down: Vec<Action>,
+    pub mouse_position: (f32, f32),
+    pub mouse_wheel: f32
+}
+
+// feeds recorded or hand written input in instead of reading the real devices, one frame at a time
+#[derive(Serialize, Deserialize, Clone, Default)]
+pub struct InputScript {
+    pub frames: VecDeque<ScriptFrame>
+}
+
+impl InputScript {
+    pub fn load(path: &str) -> Result<Self, String> {
+
+        let json = std::fs::read_to_string(path).map_err(|error| error.to_string())?;
+
+        serde_json::from_str(&json).map_err(|error| error.to_string())
+    }
+}
+
+pub struct Input {
+    pub bindings: Bindings,
+    pub bindings_path: String,
+    // None if we couldnt open the gamepad backend
+    pub gilrs: Option<Gilrs>,
+    pub state: InputState,
+    // when this is set we read from it instead of the real devices until it runs out
+    pub script: Option<InputScript>,
+    // the last gamepad button pressed this frame, used for rebinding
+    pub last_gamepad_button: Option<Button>,
+    // the last stick pushed past the threshold this frame and which way, also for rebinding
+    pub last_gamepad_axis: Option<(Axis, bool)>
+}
+
+impl Input {
+
+    pub fn new(bindings_path: &str) -> Self {
+
+        let gilrs = match Gilrs::new() {
+            Ok(gilrs) => Some(gilrs),
+            Err(error) => {
+                warn!(%error, "failed to start gamepad support");
+
+                None
+            },
+        };
+
+        Self {
+            bindings: Bindings::load(bindings_path),
+            bindings_path: bindings_path.to_string(),
+            gilrs,
+            state: InputState::default(),
+            script: None,
+            last_gamepad_button: None,
+            last_gamepad_axis: None,
+        }
+    }
+
+    // a source that only ever replays the script, for running without a window or real devices
+    pub fn scripted(script: InputScript) -> Self {
+        Self {
+            bindings: Bindings::default(),
+            bindings_path: DEFAULT_BINDINGS_PATH.to_string(),
+            gilrs: None,
+            state: InputState::default(),
+            script: Some(script),
+            last_gamepad_button: None,
+            last_gamepad_axis: None,
+        }
+    }
+
+    // call once at the start of every frame
+    pub fn update(&mut self) {
+
+        if let Some(script) = &mut self.script {
+
+            match script.frames.pop_front() {
+                Some(frame) => {
+                    self.state = InputState::from_down(
+                        frame.down.into_iter().collect(),
+                        &self.state,
+                        Vec2::new(frame.mouse_position.0, frame.mouse_position.1),
+                        frame.mouse_wheel
+                    );
+
+                    return;
+                },
+                None => {
+                    info!("input script finished");
+
+                    self.script = None;
+                },
+            }
+        }
+
+        self.last_gamepad_button = None;
+        self.last_gamepad_axis = None;
+
+        // gilrs only updates gamepad state when we take its events
+        if let Some(gilrs) = &mut self.gilrs {
+            while let Some(event) = gilrs.next_event() {
+                match event.event {
+                    EventType::ButtonPressed(button, _) => self.last_gamepad_button = Some(button),
+                    EventType::AxisChanged(axis, value, _) if value.abs() > AXIS_THRESHOLD => {
+                        self.last_gamepad_axis = Some((axis, value > 0.))
+                    },
+                    _ => {}
+                }
+            }
+        }
+
+        let down: HashSet<Action> = Action::ALL.into_iter()
+            .filter(|action| self.bindings.get(*action).iter().any(|binding| binding.is_down(&self.gilrs)))
+            .collect();
+
+        let (mouse_x, mouse_y) = mouse_position();
+
+        self.state = InputState::from_down(down, &self.state, Vec2::new(mouse_x, mouse_y), mouse_wheel().1);
+    }
+
+    // whatever was pressed this frame on any device, used when rebinding
+    pub fn last_pressed_binding(&self) -> Option<Binding> {
+
+        if let Some(binding) = get_last_key_pressed().and_then(bindable_key) {
+            return Some(binding)
+        }
+
+        if let Some(button) = MOUSE_BUTTONS.iter().copied().find(|button| is_mouse_button_pressed(*button)) {
+            return Some(Binding::mouse(button))
+        }
+
+        if let Some(button) = self.last_gamepad_button.filter(|button| GAMEPAD_BUTTONS.contains(button)) {
+            return Some(Binding::gamepad_button(button))
+        }
+
+        self.last_gamepad_axis
+            .filter(|(axis, _)| GAMEPAD_AXES.contains(axis))
+            .map(|(axis, positive)| Binding::gamepad_axis(axis, positive))
+    }
+
+    pub fn rebind(&mut self, action: Action, slot: usize, binding: Binding) {
+
+        info!(?action, slot, %binding, "rebound action");
+
+        self.bindings.replace(action, slot, binding);
+
+        self.bindings.save(&self.bindings_path);
+    }
+
+    pub fn reset_binding(&mut self, action: Action) {
+
+        self.bindings.actions.insert(action, Bindings::default_bindings(action));
+
+        self.bindings.save(&self.bindings_path);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn frame(down: Vec<Action>, mouse_position: (f32, f32)) -> ScriptFrame {
+        ScriptFrame { down, mouse_position, mouse_wheel: 0. }
+    }
+
+    #[test]
+    fn scripted_input_presses_holds_and_releases() {
+
+        let script = InputScript {
+            frames: VecDeque::from(vec![
+                frame(vec![Action::Jump], (10., 20.)),
+                frame(vec![Action::Jump, Action::MoveLeft], (15., 25.)),
+                frame(vec![Action::MoveLeft], (15., 25.)),
+                frame(vec![], (0., 0.)),
+            ])
+        };
+
+        let mut input = Input::scripted(script);
+
+        input.update();
+
+        assert!(input.state.is_pressed(Action::Jump));
+        assert!(input.state.is_down(Action::Jump));
+        assert!(!input.state.is_down(Action::MoveLeft));
+        assert_eq!((input.state.mouse_position.x, input.state.mouse_position.y), (10., 20.));
+
+        input.update();
+
+        // held, so it only counts as pressed on the first frame
+        assert!(!input.state.is_pressed(Action::Jump));
+        assert!(input.state.is_down(Action::Jump));
+        assert!(input.state.is_pressed(Action::MoveLeft));
+        assert_eq!((input.state.mouse_position.x, input.state.mouse_position.y), (15., 25.));
+
+        input.update();
+
+        assert!(input.state.is_released(Action::Jump));
+        assert!(!input.state.is_down(Action::Jump));
+        assert!(input.state.is_down(Action::MoveLeft));
+        assert!(!input.state.is_released(Action::MoveLeft));
+
+        input.update();
+
+        assert!(input.state.is_released(Action::MoveLeft));
+        assert!(input.state.down.is_empty());
+        assert!(input.state.pressed.is_empty());
+    }
+
+    #[test]
+    fn script_frames_are_used_up_in_order() {
+
+        let mut input = Input::scripted(InputScript {
+            frames: VecDeque::from(vec![frame(vec![Action::Settings], (1., 1.)), frame(vec![], (2., 2.))])
+        });
+
+        input.update();
+
+        assert_eq!(input.script.as_ref().unwrap().frames.len(), 1);
+        assert!(input.state.is_pressed(Action::Settings));
+
+        input.update();
+
+        assert!(input.script.as_ref().unwrap().frames.is_empty());
+        assert!(input.state.is_released(Action::Settings));
+    }
+
+    #[test]
+    fn script_loads_from_json() {
+
+        let path = std::env::temp_dir().join(format!("input_script_{}.json", std::process::id()));
+
+        std::fs::write(&path, r#"{"frames": [{"down": ["Jump"], "mouse_position": [3.0, 4.0], "mouse_wheel": 1.0}]}"#).unwrap();
+
+        let script = InputScript::load(path.to_str().unwrap()).unwrap();
+
+        std::fs::remove_file(&path).unwrap();
+
+        let mut input = Input::scripted(script);
+
+        input.update();
+
+        assert!(input.state.is_pressed(Action::Jump));
+        assert_eq!(input.state.mouse_wheel, 1.);
+    }
+
+    #[test]
+    fn replacing_a_binding_keeps_the_others() {
+
+        let mut bindings = Bindings::default();
+
+        bindings.replace(Action::Jump, 0, Binding::key(KeyCode::Enter));
+
+        assert_eq!(bindings.get(Action::Jump), &[
+            Binding::key(KeyCode::Enter),
+            Binding::key(KeyCode::W),
+            Binding::gamepad_button(Button::South)
+        ]);
+    }
+
+    #[test]
+    fn replacing_past_the_end_adds_a_binding() {
+
+        let mut bindings = Bindings::default();
+
+        bindings.replace(Action::SaveState, 5, Binding::gamepad_button(Button::North));
+
+        assert_eq!(bindings.get(Action::SaveState), &[Binding::key(KeyCode::F5), Binding::gamepad_button(Button::North)]);
+    }
+
+    #[test]
+    fn every_bindable_key_reads_back() {
+
+        for key in KEYS {
+            let binding = bindable_key(*key).unwrap();
+
+            assert!(matches!(&binding, Binding::Key(name) if parse_key(name) == Some(*key)));
+        }
+    }
+
+    #[test]
+    fn keys_we_cant_read_back_arent_bindable() {
+        assert_eq!(bindable_key(KeyCode::Unknown), None);
+        assert_eq!(bindable_key(KeyCode::Home), Some(Binding::key(KeyCode::Home)));
+    }
+}
@@ -1,14 +1,59 @@
+use std::collections::HashMap;
+
 use diff::Diff;
 use gamelibrary::space::Space;
 use serde::{Deserialize, Serialize};
 
-use crate::structure::Structure;
+use crate::{error::LevelError, player::PLAYER_GRAVITY, structure::Structure, world_bounds::WorldBounds};
 
 #[derive(Serialize, Deserialize, Diff, PartialEq, Clone)]
 #[diff(attr(
     #[derive(Serialize, Deserialize)]
 ))]
 pub struct Level {
-    pub structures: Vec<Structure>,
-    pub space: Space
-}
\ No newline at end of file
+    // keyed by a uuid like GameState entities
+    pub structures: HashMap<String, Structure>,
+    pub space: Space,
+    // what happens at the edges of the level
+    pub bounds: WorldBounds
+}
+
+impl Level {
+
+    pub fn empty() -> Self {
+        Self {
+            structures: HashMap::new(),
+            space: Space::new(PLAYER_GRAVITY),
+            bounds: WorldBounds::default()
+        }
+    }
+
+    // levels are stored as json so they can be diffed and fixed by hand
+    pub fn load(path: &str) -> Result<Self, LevelError> {
+
+        let level_json = std::fs::read_to_string(path)?;
+
+        Ok(serde_json::from_str(&level_json)?)
+    }
+
+    // a copy with nothing selected or being dragged, so comparing levels only compares what would be saved
+    pub fn without_editor_state(&self) -> Self {
+
+        let mut level = self.clone();
+
+        for structure in level.structures.values_mut() {
+            structure.clear_editor_state();
+        }
+
+        level
+    }
+
+    pub fn save(&self, path: &str) -> Result<(), LevelError> {
+
+        let level_json = serde_json::to_string_pretty(self)?;
+
+        std::fs::write(path, level_json)?;
+
+        Ok(())
+    }
+}
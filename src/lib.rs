@@ -1,20 +1,42 @@
 use std::collections::HashMap;
 
+use camera::Camera;
 use game_state::GameState;
-use gamelibrary::{proxies::macroquad::math::vec2::Vec2, time::Time};
+use input::InputState;
+use gamelibrary::time::Time;
 use macroquad::texture::Texture2D;
+use sound::SoundEvent;
 
 pub mod game_state;
 pub mod physics_square;
+pub mod entities;
+pub mod player;
 pub mod level;
 pub mod structure;
+pub mod network;
+pub mod chat;
+pub mod player_list;
+pub mod logging;
+pub mod error;
+pub mod assets;
+pub mod sound;
+pub mod camera;
+pub mod world_bounds;
+pub mod input;
+pub mod settings_menu;
+pub mod collaborator;
+pub mod args;
 
 pub struct TickContext<'a> {
     pub game_state: &'a mut GameState,
     pub is_host: &'a mut bool,
     pub textures: &'a mut HashMap<String, Texture2D>,
     pub sounds: &'a mut HashMap<String, macroquad::audio::Sound>,
+    // sounds entities want to play this tick. the client plays them and sends them to everyone else
+    pub sound_events: &'a mut Vec<SoundEvent>,
     pub time: &'a Time,
     pub uuid: &'a String,
-    pub camera_offset: &'a mut Vec2
+    pub camera: &'a mut Camera,
+    // entities should read input from here rather than macroquad so they can be driven by scripts
+    pub input: &'a InputState
 }
\ No newline at end of file
@@ -0,0 +1,54 @@
+use tracing_appender::{non_blocking::WorkerGuard, rolling};
+use tracing_subscriber::{fmt, layer::SubscriberExt, util::SubscriberInitExt, EnvFilter, Layer};
+
+// used when neither --log-filter or LIQUIDATORS_LOG are set
+const DEFAULT_FILTER: &str = "info";
+
+// filters use the RUST_LOG syntax so modules can be turned up individually, like "info,server::server=debug"
+fn env_filter(filter: &Option<String>) -> EnvFilter {
+
+    let filter = match filter {
+        Some(filter) => filter.clone(),
+        None => std::env::var("LIQUIDATORS_LOG").unwrap_or(DEFAULT_FILTER.to_string()),
+    };
+
+    match EnvFilter::try_new(&filter) {
+        Ok(env_filter) => env_filter,
+        Err(error) => {
+            eprintln!("invalid log filter '{}', falling back to '{}': {}", filter, DEFAULT_FILTER, error);
+
+            EnvFilter::new(DEFAULT_FILTER)
+        },
+    }
+}
+
+// sets up logging for one of the binaries. if a log directory is given we also write to a file there that rotates daily
+// the returned guard flushes the file when it is dropped so it needs to live as long as main
+pub fn init(app_name: &str, filter: Option<String>, log_directory: Option<String>) -> Option<WorkerGuard> {
+
+    let stdout_layer = fmt::layer()
+        .with_target(true)
+        .with_filter(env_filter(&filter));
+
+    let log_directory = match log_directory {
+        Some(log_directory) => log_directory,
+        None => {
+            tracing_subscriber::registry().with(stdout_layer).init();
+
+            return None;
+        },
+    };
+
+    let file_appender = rolling::daily(log_directory, format!("{}.log", app_name));
+
+    let (file_writer, guard) = tracing_appender::non_blocking(file_appender);
+
+    let file_layer = fmt::layer()
+        .with_writer(file_writer)
+        .with_ansi(false)
+        .with_filter(env_filter(&filter));
+
+    tracing_subscriber::registry().with(stdout_layer).with(file_layer).init();
+
+    Some(guard)
+}
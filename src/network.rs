@@ -0,0 +1,215 @@
+use lz4_flex::{compress_prepend_size, decompress_size_prepended};
+use std::{collections::HashMap, time::Duration};
+
+use gamelibrary::{proxies::macroquad::math::vec2::Vec2, time::Time};
+use serde::{Deserialize, Serialize};
+
+use crate::{chat::ChatMessage, collaborator::Collaborator, error::NetError, level::{Level, LevelDiff}, error::CodecError, game_state::{GameState, GameStateDiff}, player_list::PlayerInfo, sound::SoundEvent};
+
+// how many past states the server and clients keep around to use as diff baselines
+pub const STATE_HISTORY_LENGTH: usize = 128;
+
+// how long connecting to the server can take before we give up
+pub const CONNECT_TIMEOUT_SECONDS: i64 = 10;
+// how long to sleep between checks while waiting on the server, so waiting doesnt spin a whole core
+const WAIT_POLL_MILLISECONDS: u64 = 5;
+
+// everything that gets sent between the client and the server
+#[derive(Serialize, Deserialize)]
+pub enum NetworkPacket {
+    // client -> server: what changed locally since the client's last tick
+    GameStateDiff(GameStateDiff),
+    // server -> client: the server state with the given sequence number, as a diff against a state the client has acknowledged
+    StateDiff {
+        baseline: u64,
+        sequence: u64,
+        diff: GameStateDiff
+    },
+    // server -> client: used when the server has nothing the client acknowledged to diff against
+    FullState {
+        sequence: u64,
+        game_state: GameState
+    },
+    // client -> server: the client has applied the state with this sequence number
+    Ack(u64),
+    // client -> server: the client is missing the baseline of a diff and needs a full state
+    ResyncRequest,
+    Hello {
+        uuid: String,
+        name: String,
+        spectator: bool
+    },
+    ChatMessage(ChatMessage),
+    PlayerList(Vec<PlayerInfo>),
+    // the server pings with the time it sent the ping and the client sends the same time back
+    Ping(i64),
+    Pong(i64),
+    // the server is about to drop us
+    Disconnect(String),
+    // a one shot sound. clients send the ones they play and the server passes them on to everyone
+    Sound(SoundEvent),
+    // editor -> server: join the level being edited on the server instead of the game. editors dont take up a player slot
+    EditorHello {
+        uuid: String,
+        name: String
+    },
+    // server -> editor: the whole shared level, sent once when an editor joins
+    FullLevel(Level),
+    // both ways: changes to the shared level. editors send their own and the server sends them everyone else's.
+    // the websocket keeps these in order so they are diffs against whatever the other side got last, no acks needed
+    LevelDiff(LevelDiff),
+    // editor -> server: where the editor's mouse is and what they have selected. selecting a structure asks for its lock
+    EditorPresence {
+        cursor: Vec2,
+        selected: Vec<String>
+    },
+    // server -> editor: everyone editing the level, and the uuid of the editor holding the lock on each locked structure
+    Collaborators {
+        collaborators: Vec<Collaborator>,
+        locks: HashMap<String, String>
+    }
+}
+
+impl NetworkPacket {
+
+    pub fn to_bytes(&self) -> Vec<u8> {
+        // we only ever serialize our own packets so if this fails it is a bug, not bad input
+        let packet_bytes = bitcode::serialize(self).expect("failed to serialize network packet");
+
+        compress_prepend_size(&packet_bytes)
+    }
+
+    pub fn from_bytes(compressed_packet_bytes: &[u8]) -> Result<Self, CodecError> {
+        let packet_bytes = decompress_size_prepended(compressed_packet_bytes)?;
+
+        Ok(bitcode::deserialize(&packet_bytes)?)
+    }
+}
+
+// our end of a websocket to the server. the game client and the editor both talk to the server through this
+pub struct ServerConnection {
+    pub send: ewebsock::WsSender,
+    pub receive: ewebsock::WsReceiver
+}
+
+impl ServerConnection {
+
+    // opens the connection and waits for the server to accept it
+    pub fn connect(url: &str) -> Result<Self, NetError> {
+
+        let (send, receive) = ewebsock::connect(url, ewebsock::Options::default()).map_err(NetError::Connect)?;
+
+        let started = Time::now();
+
+        loop {
+            match receive.try_recv() {
+                Some(ewebsock::WsEvent::Opened) => break,
+                Some(ewebsock::WsEvent::Message(_)) => return Err(NetError::UnexpectedMessage("server sent a message before the connection opened".to_string())),
+                Some(ewebsock::WsEvent::Error(error)) => return Err(NetError::Socket(error)),
+                Some(ewebsock::WsEvent::Closed) => return Err(NetError::Closed),
+                None => Self::wait(&started)?,
+            }
+        }
+
+        Ok(Self { send, receive })
+    }
+
+    fn wait(started: &Time) -> Result<(), NetError> {
+
+        if started.elapsed().num_seconds() >= CONNECT_TIMEOUT_SECONDS {
+            return Err(NetError::Timeout);
+        }
+
+        std::thread::sleep(Duration::from_millis(WAIT_POLL_MILLISECONDS));
+
+        Ok(())
+    }
+
+    pub fn send_packet(&mut self, packet: &NetworkPacket) {
+        self.send.send(ewebsock::WsMessage::Binary(packet.to_bytes()));
+    }
+
+    // the next packet from the server if one has arrived
+    pub fn try_receive(&mut self) -> Result<Option<NetworkPacket>, NetError> {
+
+        loop {
+
+            let compressed_packet_bytes = match self.receive.try_recv() {
+                // we already got this when we connected
+                Some(ewebsock::WsEvent::Opened) => continue,
+                Some(ewebsock::WsEvent::Message(ewebsock::WsMessage::Binary(bytes))) => bytes,
+                Some(ewebsock::WsEvent::Message(_)) => return Err(NetError::UnexpectedMessage("server sent a non binary message".to_string())),
+                Some(ewebsock::WsEvent::Error(error)) => return Err(NetError::Socket(error)),
+                Some(ewebsock::WsEvent::Closed) => return Err(NetError::Closed),
+                None => return Ok(None),
+            };
+
+            return Ok(Some(NetworkPacket::from_bytes(&compressed_packet_bytes)?));
+        }
+    }
+
+    // waits for the next packet while connecting, giving up once the connection has taken too long
+    pub fn wait_for_packet(&mut self, started: &Time) -> Result<NetworkPacket, NetError> {
+
+        loop {
+            if let Some(packet) = self.try_receive()? {
+                return Ok(packet);
+            }
+
+            Self::wait(started)?;
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn hello() -> NetworkPacket {
+        NetworkPacket::Hello { uuid: "0123456789abcdef".to_string(), name: "a player with a fairly long name".to_string(), spectator: false }
+    }
+
+    #[test]
+    fn packets_survive_a_round_trip() {
+
+        let bytes = hello().to_bytes();
+
+        match NetworkPacket::from_bytes(&bytes) {
+            Ok(NetworkPacket::Hello { uuid, name, spectator }) => {
+                assert_eq!(uuid, "0123456789abcdef");
+                assert_eq!(name, "a player with a fairly long name");
+                assert!(!spectator);
+            },
+            _ => panic!("hello packet didnt come back as itself"),
+        }
+    }
+
+    #[test]
+    fn truncated_packets_fail_to_decompress() {
+
+        let bytes = hello().to_bytes();
+
+        assert!(matches!(NetworkPacket::from_bytes(&bytes[..bytes.len() / 2]), Err(CodecError::Decompress(_))));
+
+        // not even the size at the front
+        assert!(matches!(NetworkPacket::from_bytes(&bytes[..2]), Err(CodecError::Decompress(_))));
+    }
+
+    #[test]
+    fn corrupt_lz4_fails_to_decompress() {
+
+        // claims 10 bytes, then asks to copy a match from 5 bytes back before anything has been written
+        let bytes = [10, 0, 0, 0, 0x00, 0x05, 0x00];
+
+        assert!(matches!(NetworkPacket::from_bytes(&bytes), Err(CodecError::Decompress(_))));
+    }
+
+    #[test]
+    fn bad_bitcode_fails_to_deserialize() {
+
+        // valid lz4 around bytes that arent a packet
+        let bytes = compress_prepend_size(&[]);
+
+        assert!(matches!(NetworkPacket::from_bytes(&bytes), Err(CodecError::Deserialize(_))));
+    }
+}
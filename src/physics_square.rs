@@ -6,11 +6,9 @@ use gamelibrary::rigid_body::{RigidBody, RigidBodyType};
 use gamelibrary::space::{RigidBodyHandle, Space};
 use gamelibrary::traits::{Color, HasOwner, HasRigidBody};
 use diff::Diff;
-use macroquad::input::{is_key_down, KeyCode};
-use macroquad::window;
 use serde::{Deserialize, Serialize};
 
-use crate::TickContext;
+use crate::{input::Action, TickContext};
 
 #[derive(Serialize, Deserialize, Diff, PartialEq, Clone)]
 #[diff(attr(
@@ -98,20 +96,11 @@ impl PhysicsSquare {
 
     pub fn tick(&mut self, ctx: &mut TickContext) {
 
-        let rigid_body = ctx.game_state.space.get_rigid_body_mut(self.get_rigid_body_handle()).expect("shit");
-
-        if rigid_body.position.x >= window::screen_width() || rigid_body.position.x <= 0. {
-            rigid_body.velocity.x = rigid_body.velocity.x * -1.;
-        }
-
-        if rigid_body.position.y >= window::screen_height() || rigid_body.position.y <= 0. {
-            rigid_body.velocity.y = rigid_body.velocity.y * -1.;
-        }
-
+        // keeping squares inside the world is handled by the world bounds in tick_entities
         if self.controllable {
             let rigid_body = ctx.game_state.space.get_rigid_body_mut(self.get_rigid_body_handle()).expect("shit");
 
-            if is_key_down(KeyCode::W) {
+            if ctx.input.is_down(Action::MoveUp) {
 
                 if rigid_body.velocity.y.is_sign_negative() {
                     rigid_body.velocity.y = 0.
@@ -120,7 +109,7 @@ impl PhysicsSquare {
                 rigid_body.velocity.y += 4.
             }
 
-            if is_key_down(KeyCode::S) {
+            if ctx.input.is_down(Action::MoveDown) {
 
                 if rigid_body.velocity.y.is_sign_positive() {
                     rigid_body.velocity.y = 0.
@@ -129,7 +118,7 @@ impl PhysicsSquare {
                 rigid_body.velocity.y -= 4.
             }
             
-            if is_key_down(KeyCode::A) {
+            if ctx.input.is_down(Action::MoveLeft) {
 
                 if rigid_body.velocity.x.is_sign_positive() {
                     rigid_body.velocity.x = 0.
@@ -138,7 +127,7 @@ impl PhysicsSquare {
                 rigid_body.velocity.x -= 4.
             }
 
-            if is_key_down(KeyCode::D) {
+            if ctx.input.is_down(Action::MoveRight) {
 
                 if rigid_body.velocity.x.is_sign_negative() {
                     rigid_body.velocity.x = 0.
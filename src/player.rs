@@ -0,0 +1,278 @@
+use std::collections::HashMap;
+
+use diff::Diff;
+use gamelibrary::{collider::Collider, proxies::macroquad::{color::colors::WHITE, math::vec2::Vec2}, rigid_body::{RigidBody, RigidBodyType}, space::{RigidBodyHandle, Space}, traits::{Color, HasOwner, HasRigidBody}};
+use macroquad::{texture::{draw_texture_ex, DrawTextureParams, Texture2D}, window::screen_height};
+use serde::{Deserialize, Serialize};
+
+use crate::{assets::timeline, input::Action, sound::{SoundEvent, IMPACT_SOUND, JUMP_SOUND}, world_bounds::{EdgeBehavior, WorldBounds}, TickContext};
+
+// the player sprites are tiny so we draw them scaled up
+const SPRITE_SCALE: f32 = 4.;
+const SPRITE_WIDTH: f32 = 18.;
+const SPRITE_HEIGHT: f32 = 15.;
+
+// players need gravity to be able to land after jumping, so worlds with players in them are set up with this
+pub const PLAYER_GRAVITY: f32 = -980.;
+
+const RUN_SPEED: f32 = 300.;
+const JUMP_VELOCITY: f32 = 550.;
+// seconds each animation frame is shown for
+const FRAME_TIME: f32 = 0.15;
+
+#[derive(Serialize, Deserialize, Diff, PartialEq, Clone, Copy)]
+#[diff(attr(
+    #[derive(Serialize, Deserialize)]
+))]
+pub enum PlayerAnimation {
+    Idle,
+    Walk,
+    Jump
+}
+
+impl PlayerAnimation {
+    // looping animations start over when they run out of frames, the others hold their last frame
+    pub fn looping(&self) -> bool {
+        match self {
+            PlayerAnimation::Idle => true,
+            PlayerAnimation::Walk => true,
+            PlayerAnimation::Jump => false,
+        }
+    }
+
+    // texture keys for every frame in the order they play
+    pub fn frame_keys(&self, textures: &HashMap<String, Texture2D>) -> Vec<String> {
+        match self {
+            PlayerAnimation::Idle => vec!["assets/player/idle.png".to_string()],
+            PlayerAnimation::Walk => timeline(textures, "assets/player/timelines/walk"),
+            PlayerAnimation::Jump => timeline(textures, "assets/player/timelines/jump"),
+        }
+    }
+}
+
+#[derive(Serialize, Deserialize, Diff, PartialEq, Clone)]
+#[diff(attr(
+    #[derive(Serialize, Deserialize)]
+))]
+pub struct Player {
+    pub owner: String,
+    pub rigid_body_handle: RigidBodyHandle,
+    pub color: gamelibrary::proxies::macroquad::color::Color,
+    pub animation: PlayerAnimation,
+    pub animation_frame: u32,
+    // how long the current frame has been showing in seconds
+    pub frame_elapsed: f32,
+    pub facing_left: bool,
+    pub grounded: bool,
+    pub selected: bool,
+    pub dragging: bool,
+    pub drag_offset: Option<Vec2>
+}
+
+impl Player {
+
+    pub fn new(space: &mut Space, position: Vec2, owner: &String) -> Self {
+
+        let rigid_body_handle = space.insert_rigid_body(
+            RigidBody {
+                position,
+                rotation: 0.,
+                angular_velocity: 0.,
+                velocity: Vec2::new(0., 0.),
+                body_type: RigidBodyType::Dynamic,
+                owner: owner.clone(),
+                collider: Collider {
+                    hx: SPRITE_WIDTH * SPRITE_SCALE / 2.,
+                    hy: SPRITE_HEIGHT * SPRITE_SCALE / 2.,
+                    restitution: 0.,
+                    mass: 10.,
+                    owner: owner.clone()
+                }
+            }
+        );
+
+        Self {
+            owner: owner.clone(),
+            rigid_body_handle,
+            color: WHITE,
+            animation: PlayerAnimation::Idle,
+            animation_frame: 0,
+            frame_elapsed: 0.,
+            facing_left: false,
+            grounded: false,
+            selected: false,
+            dragging: false,
+            drag_offset: None
+        }
+    }
+
+    // check a few points just under our feet for anything solid that isnt us
+    pub fn update_grounded(&mut self, space: &mut Space, bounds: &WorldBounds) {
+
+        let rigid_body = space.get_rigid_body_mut(&self.rigid_body_handle).unwrap();
+
+        let feet_y = rigid_body.position.y - rigid_body.collider.hy - 2.;
+
+        // the bottom of the world counts as ground when it is a wall
+        if bounds.bottom == EdgeBehavior::Wall && feet_y <= bounds.min.y {
+            self.grounded = true;
+
+            return;
+        }
+
+        let probes = [
+            Vec2::new(rigid_body.position.x - rigid_body.collider.hx + 2., feet_y),
+            Vec2::new(rigid_body.position.x, feet_y),
+            Vec2::new(rigid_body.position.x + rigid_body.collider.hx - 2., feet_y),
+        ];
+
+        self.grounded = probes.into_iter().any(|probe| {
+            space.query_point(probe).iter().any(|handle| *handle != self.rigid_body_handle)
+        });
+    }
+
+    pub fn tick(&mut self, ctx: &mut TickContext) {
+
+        let delta = ctx.time.elapsed().num_milliseconds() as f32 / 1000.;
+
+        let was_grounded = self.grounded;
+
+        self.update_grounded(&mut ctx.game_state.space, &ctx.game_state.bounds);
+
+        let rigid_body = ctx.game_state.space.get_rigid_body_mut(&self.rigid_body_handle).unwrap();
+
+        if self.grounded && !was_grounded {
+            ctx.sound_events.push(SoundEvent::sfx(IMPACT_SOUND, rigid_body.position.clone(), 0.6));
+
+            ctx.camera.add_shake(0.3);
+        }
+
+        let mut direction = 0.;
+
+        if ctx.input.is_down(Action::MoveLeft) {
+            direction -= 1.;
+        }
+
+        if ctx.input.is_down(Action::MoveRight) {
+            direction += 1.;
+        }
+
+        rigid_body.velocity.x = direction * RUN_SPEED;
+
+        if direction < 0. {
+            self.facing_left = true;
+        }
+
+        if direction > 0. {
+            self.facing_left = false;
+        }
+
+        if self.grounded && ctx.input.is_pressed(Action::Jump) {
+            rigid_body.velocity.y = JUMP_VELOCITY;
+
+            self.grounded = false;
+
+            ctx.sound_events.push(SoundEvent::sfx(JUMP_SOUND, rigid_body.position.clone(), 1.));
+        }
+
+        // keep the player upright no matter what they bump into
+        rigid_body.rotation = 0.;
+        rigid_body.angular_velocity = 0.;
+
+        let animation = if !self.grounded {
+            PlayerAnimation::Jump
+        } else if direction != 0. {
+            PlayerAnimation::Walk
+        } else {
+            PlayerAnimation::Idle
+        };
+
+        if animation != self.animation {
+            self.animation = animation;
+            self.animation_frame = 0;
+            self.frame_elapsed = 0.;
+        }
+
+        self.frame_elapsed += delta;
+
+        if self.frame_elapsed >= FRAME_TIME {
+            self.frame_elapsed -= FRAME_TIME;
+            self.animation_frame += 1;
+        }
+    }
+
+    pub async fn draw(&mut self, camera_offset: &Vec2, space: &mut Space, textures: &HashMap<String, Texture2D>) {
+
+        let frame_keys = self.animation.frame_keys(textures);
+
+        if frame_keys.is_empty() {
+            // the textures arent loaded so fall back to a plain rectangle
+            HasRigidBody::draw(self, camera_offset, space).await;
+
+            return;
+        }
+
+        let frame = match self.animation.looping() {
+            true => self.animation_frame as usize % frame_keys.len(),
+            false => (self.animation_frame as usize).min(frame_keys.len() - 1),
+        };
+
+        let texture = match textures.get(&frame_keys[frame]) {
+            Some(texture) => texture,
+            None => return,
+        };
+
+        let rigid_body = space.get_rigid_body_mut(&self.rigid_body_handle).unwrap();
+
+        // world coordinates have y going up so flip them into screen space
+        let x = rigid_body.position.x - camera_offset.x - rigid_body.collider.hx;
+        let y = screen_height() - (rigid_body.position.y - camera_offset.y) - rigid_body.collider.hy;
+
+        draw_texture_ex(
+            texture,
+            x,
+            y,
+            macroquad::color::WHITE,
+            DrawTextureParams {
+                dest_size: Some(macroquad::math::Vec2::new(rigid_body.collider.hx * 2., rigid_body.collider.hy * 2.)),
+                flip_x: self.facing_left,
+                ..Default::default()
+            }
+        );
+    }
+}
+
+impl Color for Player {
+    fn color(&mut self) -> &mut gamelibrary::proxies::macroquad::color::Color {
+        &mut self.color
+    }
+}
+
+impl HasRigidBody for Player {
+
+    fn get_rigid_body_handle(&self) -> &RigidBodyHandle {
+        &self.rigid_body_handle
+    }
+
+    fn get_drag_offset(&mut self) -> &mut Option<Vec2> {
+        &mut self.drag_offset
+    }
+
+    fn get_selected(&mut self) -> &mut bool {
+        &mut self.selected
+    }
+
+    fn get_dragging(&mut self) -> &mut bool {
+        &mut self.dragging
+    }
+}
+
+impl HasOwner for Player {
+    fn get_owner(&self) -> String {
+        self.owner.clone()
+    }
+
+    fn set_owner(&mut self, uuid: String) {
+        self.owner = uuid
+    }
+}
@@ -0,0 +1,42 @@
+use macroquad::{color::{Color, LIGHTGRAY, WHITE}, shapes::draw_rectangle, text::draw_text, window::{screen_height, screen_width}};
+use serde::{Deserialize, Serialize};
+
+const ROW_HEIGHT: f32 = 25.;
+const WIDTH: f32 = 400.;
+
+#[derive(Serialize, Deserialize, Clone)]
+pub struct PlayerInfo {
+    pub uuid: String,
+    pub name: String,
+    // round trip time in milliseconds
+    pub ping: i64,
+    pub square_count: usize,
+    pub spectator: bool
+}
+
+pub fn draw_player_list(players: &Vec<PlayerInfo>) {
+
+    let height = ROW_HEIGHT * (players.len() + 1) as f32 + 10.;
+
+    let x = (screen_width() - WIDTH) / 2.;
+    let y = (screen_height() - height) / 3.;
+
+    draw_rectangle(x, y, WIDTH, height, Color::new(0., 0., 0., 0.6));
+
+    draw_text("Name", x + 10., y + ROW_HEIGHT, 20., LIGHTGRAY);
+    draw_text("Ping", x + 250., y + ROW_HEIGHT, 20., LIGHTGRAY);
+    draw_text("Squares", x + 320., y + ROW_HEIGHT, 20., LIGHTGRAY);
+
+    for (row, player) in players.iter().enumerate() {
+
+        let row_y = y + ROW_HEIGHT * (row + 2) as f32;
+
+        match player.spectator {
+            true => draw_text(&format!("{} (spectating)", player.name), x + 10., row_y, 20., LIGHTGRAY),
+            false => draw_text(&player.name, x + 10., row_y, 20., WHITE),
+        };
+
+        draw_text(&format!("{}ms", player.ping), x + 250., row_y, 20., WHITE);
+        draw_text(&player.square_count.to_string(), x + 320., row_y, 20., WHITE);
+    }
+}
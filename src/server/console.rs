@@ -0,0 +1,115 @@
+use std::{io::{BufRead, BufReader, Write}, net::{SocketAddr, TcpListener}, sync::mpsc::{self, Receiver, Sender}};
+
+use tracing::{error, info, warn};
+
+pub struct ConsoleCommand {
+    pub line: String,
+    // where to send the output of the command. None means print it
+    pub reply: Option<Sender<String>>
+}
+
+// reads admin commands from stdin and optionally from a local tcp socket on background threads
+pub struct Console {
+    pub receiver: Receiver<ConsoleCommand>
+}
+
+impl Console {
+
+    pub fn start(socket_address: Option<SocketAddr>) -> Self {
+
+        let (sender, receiver) = mpsc::channel();
+
+        let stdin_sender = sender.clone();
+
+        std::thread::spawn(move || {
+            for line in std::io::stdin().lines() {
+
+                let line = match line {
+                    Ok(line) => line,
+                    Err(error) => {
+                        warn!(%error, "failed to read from stdin, console input is disabled");
+
+                        return;
+                    },
+                };
+
+                // the server has shut down
+                if stdin_sender.send(ConsoleCommand { line, reply: None }).is_err() {
+                    return;
+                }
+            }
+        });
+
+        if let Some(socket_address) = socket_address {
+            Self::listen(socket_address, sender);
+        }
+
+        Self {
+            receiver
+        }
+    }
+
+    // lets something like netcat run commands: every line is a command and every command gets a reply
+    fn listen(socket_address: SocketAddr, sender: Sender<ConsoleCommand>) {
+
+        let listener = match TcpListener::bind(socket_address) {
+            Ok(listener) => listener,
+            Err(error) => {
+                // the server still works without it, commands can still be typed into stdin
+                error!(%socket_address, %error, "failed to bind console socket, the socket console is disabled");
+
+                return;
+            },
+        };
+
+        info!(%socket_address, "admin console listening");
+
+        std::thread::spawn(move || {
+            for stream in listener.incoming() {
+
+                let mut stream = match stream {
+                    Ok(stream) => stream,
+                    Err(error) => {
+                        warn!(%error, "failed to accept console connection");
+
+                        continue;
+                    },
+                };
+
+                let sender = sender.clone();
+
+                std::thread::spawn(move || {
+
+                    let reader = BufReader::new(stream.try_clone().expect("failed to clone console stream"));
+
+                    for line in reader.lines() {
+
+                        let line = match line {
+                            Ok(line) => line,
+                            Err(_) => return,
+                        };
+
+                        let (reply_sender, reply_receiver) = mpsc::channel();
+
+                        if sender.send(ConsoleCommand { line, reply: Some(reply_sender) }).is_err() {
+                            return;
+                        }
+
+                        let reply = match reply_receiver.recv() {
+                            Ok(reply) => reply,
+                            Err(_) => return,
+                        };
+
+                        if writeln!(stream, "{}", reply).is_err() {
+                            return;
+                        }
+                    }
+                });
+            }
+        });
+    }
+
+    pub fn try_recv(&self) -> Option<ConsoleCommand> {
+        self.receiver.try_recv().ok()
+    }
+}
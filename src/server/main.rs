@@ -1,15 +1,42 @@
 use std::{net::SocketAddr, str::FromStr};
 
+use liquidators_lib::{args::{arg_value, parse_arg}, logging};
+use tracing::error;
+
 
 
 pub mod server;
+pub mod console;
 
 fn main () {
 
+    let args: Vec<String> = std::env::args().collect();
+
+    // keep this alive until the server exits or we lose buffered file logs
+    let _log_guard = logging::init("server", arg_value(&args, "--log-filter"), arg_value(&args, "--log-dir"));
+
+    // spectators dont count towards this
+    let max_players = parse_arg(&args, "--max-players").unwrap_or(8);
+
+    // the admin console can also be reached over a local socket with --console-port <port>
+    let console_address = parse_arg(&args, "--console-port").map(|port| {
+        SocketAddr::new(std::net::IpAddr::from_str("127.0.0.1").expect("failed to parse ip"), port)
+    });
+
     let mut server = server::Server::new(
-        SocketAddr::new(std::net::IpAddr::from_str("0.0.0.0").expect("failed to parse ip"), 5556)
+        SocketAddr::new(std::net::IpAddr::from_str("0.0.0.0").expect("failed to parse ip"), 5556),
+        max_players,
+        console_address
     );
-    
+
+    // start from a level made in the editor instead of an empty world
+    if let Some(level_path) = arg_value(&args, "--level") {
+        if let Err(error) = server.load_level(&level_path) {
+            error!(path = %level_path, %error, "failed to load level");
+
+            return;
+        }
+    }
+
     server.run();
 }
-
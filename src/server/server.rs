@@ -1,19 +1,114 @@
-use std::net::{SocketAddr, TcpListener, TcpStream};
+use std::{collections::HashMap, net::{SocketAddr, TcpListener, TcpStream}, time::Duration};
 
 use diff::Diff;
-use liquidators_lib::game_state::{GameState, GameStateDiff};
-use lz4_flex::{compress_prepend_size, decompress_size_prepended};
+use gamelibrary::{proxies::macroquad::math::vec2::Vec2, time::Time, traits::HasOwner};
+use liquidators_lib::{chat::ChatMessage, collaborator::Collaborator, entities::Entity, error::{LevelError, NetError}, game_state::GameState, level::Level, network::{NetworkPacket, STATE_HISTORY_LENGTH}, player::PLAYER_GRAVITY, player_list::PlayerInfo};
+use tracing::{debug, debug_span, info, info_span, warn};
 use tungstenite::{Message, WebSocket};
 
+use crate::console::Console;
+
+// the owner of everything the server simulates itself, like the structures in a level
+pub const SERVER_OWNER: &str = "server";
+
+pub struct ConnectedClient {
+    pub websocket: WebSocket<TcpStream>,
+    // we dont know who the client is until they send us a hello packet
+    pub uuid: Option<String>,
+    pub name: String,
+    pub ping: i64,
+    // spectators receive updates but never own anything and dont take up a player slot
+    pub spectator: bool,
+    // the latest state the client has told us it applied. this is what we diff against when sending them updates
+    pub acked_sequence: Option<u64>,
+    pub sent_sequence: Option<u64>,
+    pub last_state_send: Time,
+    // editors work on the shared level instead of playing and dont get game state updates
+    pub editor: bool,
+    // the shared level as this editor has it. this is what we diff against when sending them changes
+    pub level_baseline: Option<Level>,
+    // where the editor's mouse is in the level
+    pub cursor: Vec2,
+    pub selected: Vec<String>,
+    pub disconnected: bool
+}
+
+impl ConnectedClient {
+    pub fn new(websocket: WebSocket<TcpStream>) -> Self {
+        Self {
+            websocket,
+            uuid: None,
+            name: "unknown".to_string(),
+            ping: 0,
+            spectator: false,
+            acked_sequence: None,
+            sent_sequence: None,
+            last_state_send: Time::now(),
+            editor: false,
+            level_baseline: None,
+            cursor: Vec2::ZERO,
+            selected: vec![],
+            disconnected: false
+        }
+    }
+
+    pub fn send(&mut self, compressed_packet_bytes: Vec<u8>) {
+        match self.websocket.send(Message::Binary(compressed_packet_bytes)) {
+            Ok(_) => {},
+            Err(error) => {
+                match error {
+                    tungstenite::Error::Io(io_error) if io_error.kind() == std::io::ErrorKind::WouldBlock => {
+                        // the message is queued and will get flushed the next time we write to this client
+                    },
+                    _ => {
+                        warn!(client = %self.name, %error, "failed to send packet, dropping client");
+
+                        self.disconnected = true;
+                    }
+                }
+            },
+        }
+    }
+
+    pub fn kick(&mut self, reason: String) {
+        self.send(NetworkPacket::Disconnect(reason).to_bytes());
+
+        // closing flushes whatever we have queued for this client, including the disconnect reason
+        let _ = self.websocket.close(None);
+
+        self.disconnected = true;
+    }
+}
+
 pub struct Server {
     pub listener: TcpListener,
-    pub clients: Vec<WebSocket<TcpStream>>,
+    pub clients: Vec<ConnectedClient>,
+    pub max_players: usize,
     pub game_state: GameState,
-    pub update_history: Vec<GameState>
+    // sequence number of the current game state. goes up every time the state changes
+    pub sequence: u64,
+    pub update_history: Vec<(u64, GameState)>,
+    // packets that need to go to every client once all the clients have been read from
+    pub pending_broadcasts: Vec<NetworkPacket>,
+    pub player_list_dirty: bool,
+    // the level editors are working on together. it is kept apart from the game state so editing doesnt disturb anyone playing
+    pub shared_level: Level,
+    // structure id -> uuid of the editor that has it selected. nobody else can select it until they let go of it
+    pub locks: HashMap<String, String>,
+    pub collaborators_dirty: bool,
+    pub last_ping: Time,
+    pub console: Console,
+    // how many times a second the server loop runs. 0 means as fast as possible
+    pub tick_rate: u32,
+    pub tick_count: u64,
+    // when the space was last stepped, for moving kinematic structures and friction
+    pub last_step: Time,
+    pub packets_received: u64,
+    pub start_time: Time
 }
 
 impl Server {
-    pub fn new(address: SocketAddr) -> Self {
+    pub fn new(address: SocketAddr, max_players: usize, console_address: Option<SocketAddr>) -> Self {
 
         let listener = match TcpListener::bind(address) {
             Ok(listener) => listener,
@@ -28,8 +123,24 @@ impl Server {
         Self {
             listener,
             clients: vec![],
-            game_state: GameState::empty(),
-            update_history: vec![],
+            max_players,
+            // players spawn straight into this world so it needs gravity
+            game_state: GameState::with_gravity(PLAYER_GRAVITY),
+            sequence: 0,
+            update_history: vec![(0, GameState::with_gravity(PLAYER_GRAVITY))],
+            pending_broadcasts: vec![],
+            player_list_dirty: false,
+            shared_level: Level::empty(),
+            locks: HashMap::new(),
+            collaborators_dirty: false,
+            last_ping: Time::now(),
+            console: Console::start(console_address),
+            // unthrottled until someone sets a rate from the console
+            tick_rate: 0,
+            tick_count: 0,
+            last_step: Time::now(),
+            packets_received: 0,
+            start_time: Time::now()
         }
 
         
@@ -38,160 +149,720 @@ impl Server {
     pub fn run(&mut self) {
 
         loop {
+
+            let tick_start = Time::now();
+
+            let _tick_span = debug_span!("tick", tick = self.tick_count).entered();
+
+            self.handle_console_commands();
             
-            self.accept_new_client();
+            if let Err(error) = self.accept_new_client() {
+                warn!(%error, "failed to accept a new client");
+            }
+
+            debug_span!("receive").in_scope(|| {
+                self.receive_updates();
+            });
+
+            self.remove_disconnected_clients();
+
+            // clients only simulate what they own, so the server has to move the level's dynamic structures itself
+            debug_span!("physics_step").in_scope(|| {
+                self.game_state.space.step(&SERVER_OWNER.to_string());
+
+                // the loop can run faster than a millisecond a tick
+                let delta = self.last_step.elapsed().num_microseconds().unwrap_or(0) as f32 / 1_000_000.;
+
+                // nobody else moves, slows down or keeps inside the world the structures we own
+                self.game_state.settle_owned(&SERVER_OWNER.to_string(), delta);
+
+                self.last_step = Time::now();
+            });
+
+            self.snapshot_game_state();
 
-            self.receive_updates();
+            debug_span!("send").in_scope(|| {
+                self.send_state_updates();
+
+                self.send_level_updates();
+
+                self.ping_clients();
+
+                self.flush_broadcasts();
+            });
+
+            self.tick_count += 1;
 
             // slow the loop down a bit so that it doesnt use so much cpu
-            //std::thread::sleep(Duration::from_millis(1));
+            if self.tick_rate != 0 {
+                let remaining = 1000 / self.tick_rate as i64 - tick_start.elapsed().num_milliseconds();
+
+                if remaining > 0 {
+                    std::thread::sleep(Duration::from_millis(remaining as u64));
+                }
+            }
             
         }
     }
 
     pub fn receive_updates(&mut self) {
 
-        'client_loop: for client_index in 0..self.clients.len() {
+        for client_index in 0..self.clients.len() {
 
             // take the client out, receive all updates, then put it back in
             let mut client = self.clients.remove(client_index);
-            
-            // keep trying to receive updates until there are none
-            loop {
-
-                let compressed_game_state_diff_bytes = match client.read() {
-                    Ok(message) => {
-                        match message {
-                            Message::Binary(game_state_diff_bytes) => {
-                                game_state_diff_bytes
-                            },
-                            _ => todo!("client tried to send non binary message")
-                        }
-                    },
-                    Err(error) => {
-                        match error {
-
-                            tungstenite::Error::Io(io_error) => {
-                                match io_error.kind() {
-                                    std::io::ErrorKind::WouldBlock => {
-                                        // this means that there was no update to read
-                                        self.clients.insert(client_index, client);
-                                        
-                                        continue 'client_loop // move to the next client
-                                    },
-                                    _ => todo!("unhandled io error: {}", io_error),
-                                }
-                            },
-                            _ => todo!("unhandled websocket message read error: {}", error)
-                        }
-                    },
-                };
-                let game_state_diff_bytes = decompress_size_prepended(&compressed_game_state_diff_bytes).expect("Failed to decompress game state diff string bytes");
-    
-                let game_state_diff: GameStateDiff = match bitcode::deserialize(&game_state_diff_bytes) {
-                    Ok(game_state_diff) => game_state_diff,
-                    Err(error) => {
-                        todo!("unhandled game state diff deserialization error: {}", error);
-                    },
-                };
-    
-                // relay this update to other clients
-                'relay: for other_client_index in 0..self.clients.len() {
-    
-                    let mut other_client = self.clients.remove(other_client_index);
-    
-                    match other_client.send(Message::Binary(compressed_game_state_diff_bytes.clone())) {
-                        Ok(_) => {
-                            self.clients.insert(other_client_index, other_client);
-
-                            continue 'relay;
 
-                        },
-                        Err(error) => {
-                            todo!("unhandled error when relaying update data to client: {}", error);
-    
-                        },
-                    }
-    
-                }
+            match self.receive_client_updates(&mut client) {
+                Ok(_) => {},
+                Err(NetError::Closed) => client.disconnected = true,
+                // one bad packet isnt worth dropping the client over
+                Err(NetError::Codec(error)) => warn!(client = %client.name, %error, "received a malformed packet"),
+                Err(error) => {
+                    warn!(client = %client.name, %error, "failed to receive updates, dropping client");
 
-                // apply it to our own game state
-                self.game_state.apply(&game_state_diff);
+                    client.disconnected = true;
+                },
             }
+
+            self.clients.insert(client_index, client);
         }
     }
 
-    pub fn accept_new_client(&mut self) -> Option<()>{
-
-        match self.listener.accept() {
-            Ok((stream, address)) => {
-                println!("received new connection from address: {}", address);
+    pub fn receive_client_updates(&mut self, client: &mut ConnectedClient) -> Result<(), NetError> {
 
-                stream.set_nonblocking(true).expect("Failed to set new client as non blocking");
+        // keep trying to receive updates until there are none
+        loop {
 
-                let mut websocket_stream = loop {
-                    match tungstenite::accept(stream.try_clone().expect("failed to clone stream")) {
-                        Ok(websocket_stream) => break websocket_stream,
-                        Err(error) => {
-                            match error {
-                                tungstenite::HandshakeError::Interrupted(_) => continue, // try again if the handshake isnt done yet
-                                tungstenite::HandshakeError::Failure(error) => panic!("handshake failed with new client: {}", error),
-                            }
+            let compressed_packet_bytes = match client.websocket.read() {
+                Ok(message) => {
+                    match message {
+                        Message::Binary(packet_bytes) => {
+                            packet_bytes
                         },
+                        Message::Close(_) => return Err(NetError::Closed),
+                        // tungstenite answers pings for us
+                        Message::Ping(_) | Message::Pong(_) => continue,
+                        _ => return Err(NetError::UnexpectedMessage("client sent a non binary message".to_string()))
+                    }
+                },
+                Err(tungstenite::Error::Io(io_error)) if io_error.kind() == std::io::ErrorKind::WouldBlock => {
+                    // this means that there was no update to read
+                    return Ok(())
+                },
+                Err(error) => return Err(error.into()),
+            };
+
+            self.packets_received += 1;
+
+            match NetworkPacket::from_bytes(&compressed_packet_bytes)? {
+                NetworkPacket::GameStateDiff(game_state_diff) => {
+
+                    if client.spectator {
+                        warn!(client = %client.name, "spectator tried to send a game state update, ignoring it");
+
+                        continue;
+                    }
+
+                    // this gets sent on to other clients as part of the next state update
+                    self.game_state.apply(&game_state_diff);
+                },
+                NetworkPacket::Hello { uuid, name, spectator } => {
+
+                    if !spectator && self.player_count() >= self.max_players {
+                        info!(%name, max_players = self.max_players, "rejected player because the server is full");
+
+                        client.kick(format!("the server is full ({} players)", self.max_players));
+
+                        return Ok(())
+                    }
+
+                    info!(%name, %uuid, spectator, "client joined");
+
+                    client.uuid = Some(uuid);
+                    client.name = name.clone();
+                    client.spectator = spectator;
+
+                    let join_message = match spectator {
+                        true => format!("{} is now spectating", name),
+                        false => format!("{} joined the game", name),
                     };
-                };
-                
-
-                // send client current state
-                let game_state_string = serde_json::to_string(&self.game_state).expect("Failed to serialize current game state");
-
-                let game_state_bytes = game_state_string.as_bytes().to_vec();
-
-                let compressed_game_state_bytes = compress_prepend_size(&game_state_bytes);
-
-                // keep attempting to send initial state to client
-                loop {
-                    match websocket_stream.send(
-                        Message::Binary(compressed_game_state_bytes.clone())
-                    ) {
-                        Ok(_) => break,
-                        Err(error) => {
-                            match error {
-                                tungstenite::Error::Io(io_error) => {
-                                    match io_error.kind() {
-                                        std::io::ErrorKind::WouldBlock => {
-                                            continue; // try again if the socket blocked
-                                        },
-                                        _ => panic!("Something went wrong trying to send initial state: {}", io_error)
-                                    }
-                                },
-                                _ => panic!("Something went wrong trying to send initial state: {}", error)
-                            }
+
+                    self.pending_broadcasts.push(
+                        NetworkPacket::ChatMessage(ChatMessage::system(join_message))
+                    );
+
+                    self.player_list_dirty = true;
+                },
+                NetworkPacket::ChatMessage(message) => {
+                    // use the name we have on record so clients cant pretend to be someone else
+                    self.pending_broadcasts.push(
+                        NetworkPacket::ChatMessage(ChatMessage { author: Some(client.name.clone()), text: message.text })
+                    );
+                },
+                NetworkPacket::Ack(sequence) => {
+                    client.acked_sequence = Some(client.acked_sequence.unwrap_or(0).max(sequence));
+                },
+                NetworkPacket::ResyncRequest => {
+                    debug!(client = %client.name, "client asked for a full state");
+
+                    client.acked_sequence = None;
+                    client.sent_sequence = None;
+                },
+                NetworkPacket::Pong(sent_time) => {
+                    client.ping = chrono::Utc::now().timestamp_millis() - sent_time;
+                },
+                NetworkPacket::Sound(mut sound_event) => {
+
+                    if client.spectator {
+                        continue;
+                    }
+
+                    // the client that played it already heard it
+                    sound_event.source = client.uuid.clone().unwrap_or_default();
+
+                    self.pending_broadcasts.push(NetworkPacket::Sound(sound_event));
+                },
+                NetworkPacket::EditorHello { uuid, name } => {
+
+                    info!(%name, %uuid, "editor joined");
+
+                    client.uuid = Some(uuid);
+                    client.name = name;
+                    client.editor = true;
+
+                    client.send(NetworkPacket::FullLevel(self.shared_level.clone()).to_bytes());
+
+                    client.level_baseline = Some(self.shared_level.clone());
+
+                    self.collaborators_dirty = true;
+                },
+                NetworkPacket::LevelDiff(level_diff) => {
+
+                    let level_baseline = match &mut client.level_baseline {
+                        Some(level_baseline) => level_baseline,
+                        None => {
+                            warn!(client = %client.name, "client tried to edit the shared level without joining as an editor, ignoring it");
+
+                            continue;
                         },
+                    };
+
+                    // the editor already has their own changes, so they go in their baseline too or they would get them back.
+                    // anything we throw away below then gets undone on their side by the next level update
+                    level_baseline.apply(&level_diff);
+
+                    let mut edited_level = self.shared_level.clone();
+
+                    edited_level.apply(&level_diff);
+
+                    let uuid = client.uuid.clone().unwrap_or_default();
+
+                    if self.restore_locked_structures(&mut edited_level, &uuid) {
+                        warn!(client = %client.name, "editor changed structures someone else is editing, undoing those changes");
+                    }
+
+                    // this gets sent on to the other editors with the next level update
+                    self.shared_level = edited_level;
+
+                    self.release_deleted_locks();
+                },
+                NetworkPacket::EditorPresence { cursor, selected } => {
+
+                    if !client.editor {
+                        continue;
                     }
+
+                    client.cursor = cursor;
+                    client.selected = selected;
+
+                    self.update_locks(client);
+
+                    self.collaborators_dirty = true;
+                },
+                NetworkPacket::Ping(_) | NetworkPacket::PlayerList(_) | NetworkPacket::Disconnect(_) | NetworkPacket::StateDiff { .. } | NetworkPacket::FullState { .. } | NetworkPacket::FullLevel(_) | NetworkPacket::Collaborators { .. } => {
+                    warn!(client = %client.name, "client sent a packet that only the server should send");
+                },
+            }
+        }
+    }
+
+    pub fn handle_console_commands(&mut self) {
+
+        while let Some(command) = self.console.try_recv() {
+
+            let _command_span = info_span!("console_command", line = %command.line).entered();
+
+            let output = self.run_command(&command.line);
+
+            match command.reply {
+                Some(reply) => {
+                    // the console connection might have closed while we were running the command
+                    let _ = reply.send(output);
+                },
+                None => println!("{}", output),
+            }
+        }
+    }
+
+    pub fn run_command(&mut self, line: &str) -> String {
+
+        let (command, argument) = match line.trim().split_once(' ') {
+            Some((command, argument)) => (command, argument.trim()),
+            None => (line.trim(), ""),
+        };
+
+        match command {
+            "help" => "commands: list, kick <player>, say <message>, save [file], load <file>, level <file>, clear, tickrate <n>, stats".to_string(),
+            "list" => {
+                let lines: Vec<String> = self.clients.iter().map(|client| {
+                    format!(
+                        "{} ({}) ping: {}ms{}{}",
+                        client.name,
+                        client.uuid.clone().unwrap_or("no hello yet".to_string()),
+                        client.ping,
+                        if client.spectator { " [spectator]" } else { "" },
+                        if client.editor { " [editor]" } else { "" }
+                    )
+                }).collect();
+
+                match lines.is_empty() {
+                    true => "no clients connected".to_string(),
+                    false => lines.join("\n"),
                 }
+            },
+            "kick" => {
+                // players can be kicked by name or uuid
+                let client = self.clients.iter_mut().find(|client| {
+                    client.name.eq_ignore_ascii_case(argument) || client.uuid.as_deref() == Some(argument)
+                });
 
-                println!("pushing new client");
+                match client {
+                    Some(client) => {
+                        client.kick("you were kicked by an admin".to_string());
 
-                self.clients.push(websocket_stream);
+                        format!("kicked {}", client.name)
+                    },
+                    None => format!("no player called '{}'", argument),
+                }
+            },
+            "say" => {
+                if argument.is_empty() {
+                    return "usage: say <message>".to_string();
+                }
 
-                return Some(())
+                self.pending_broadcasts.push(
+                    NetworkPacket::ChatMessage(ChatMessage { author: Some("Server".to_string()), text: argument.to_string() })
+                );
 
+                format!("Server: {}", argument)
             },
-            Err(error) => {
-                match error.kind() {
-                    std::io::ErrorKind::WouldBlock => return None, // no new clients
+            "save" => {
+                let path = if argument.is_empty() { "server_state.json" } else { argument };
 
-                    _ => {
-                        println!("Something went wrong trying to accept a new client");
-                        return None
-                    }
+                let game_state_json = serde_json::to_string_pretty(&self.game_state).expect("failed to serialize game state");
+
+                match std::fs::write(path, game_state_json) {
+                    Ok(_) => format!("saved game state to {}", path),
+                    Err(error) => format!("failed to save game state to {}: {}", path, error),
+                }
+            },
+            "load" => {
+                if argument.is_empty() {
+                    return "usage: load <file>".to_string();
+                }
+
+                let game_state_json = match std::fs::read_to_string(argument) {
+                    Ok(game_state_json) => game_state_json,
+                    Err(error) => return format!("failed to read {}: {}", argument, error),
+                };
+
+                let game_state: GameState = match serde_json::from_str(&game_state_json) {
+                    Ok(game_state) => game_state,
+                    Err(error) => return format!("failed to deserialize {}: {}", argument, error),
+                };
+
+                self.replace_game_state(game_state);
+
+                format!("loaded game state from {}", argument)
+            },
+            "level" => {
+                match self.load_level(argument) {
+                    Ok(()) => format!("started level {}", argument),
+                    Err(error) => format!("failed to load level {}: {}", argument, error),
+                }
+            },
+            "clear" => {
+                self.replace_game_state(GameState::with_gravity(PLAYER_GRAVITY));
+
+                "cleared the game state".to_string()
+            },
+            "tickrate" => {
+                match argument.parse() {
+                    Ok(tick_rate) => {
+                        self.tick_rate = tick_rate;
+
+                        format!("tick rate set to {}", tick_rate)
+                    },
+                    Err(_) => format!("tick rate is {}. usage: tickrate <n> (0 for unlimited)", self.tick_rate),
                 }
             },
+            "stats" => {
+                let uptime = self.start_time.elapsed().num_seconds().max(1);
+
+                format!(
+                    "uptime: {}s\nplayers: {}/{}\nspectators: {}\nsquares: {}\ntick rate: {} (actual {} ticks/s)\npackets received: {}",
+                    uptime,
+                    self.player_count(),
+                    self.max_players,
+                    self.clients.iter().filter(|client| client.spectator).count(),
+                    self.game_state.entities.values().filter(|entity| matches!(entity, Entity::PhysicsSquare(_))).count(),
+                    self.tick_rate,
+                    self.tick_count / uptime as u64,
+                    self.packets_received
+                )
+            },
+            "" => String::new(),
+            _ => format!("unknown command '{}', try 'help'", command),
         }
     }
 
+    // replaces the whole game state with a fresh copy of the level. the server owns and simulates the level's structures
+    pub fn load_level(&mut self, path: &str) -> Result<(), LevelError> {
 
+        let level = Level::load(path)?;
 
-}
+        info!(%path, structures = level.structures.len(), "loaded level");
+
+        self.replace_game_state(GameState::from_level(&level, &SERVER_OWNER.to_string()));
+
+        Ok(())
+    }
+
+    // swap out the whole game state. clients get the new one with the next state update
+    pub fn replace_game_state(&mut self, game_state: GameState) {
+        self.game_state = game_state;
+
+        self.player_list_dirty = true;
+    }
+
+    // save the current state to the history if it changed so it can be used as a baseline later
+    pub fn snapshot_game_state(&mut self) {
+
+        let (_, latest_game_state) = self.update_history.last().expect("the history should never be empty");
+
+        if *latest_game_state == self.game_state {
+            return;
+        }
+
+        self.sequence += 1;
+
+        self.update_history.push((self.sequence, self.game_state.clone()));
+
+        if self.update_history.len() > STATE_HISTORY_LENGTH {
+            self.update_history.remove(0);
+        }
+    }
+
+    // send every client the current state as a diff against the last state they acknowledged
+    pub fn send_state_updates(&mut self) {
+
+        for client in self.clients.iter_mut() {
+
+            if client.editor {
+                continue;
+            }
+
+            if client.acked_sequence == Some(self.sequence) {
+                continue;
+            }
+
+            // give the client a second to acknowledge before we assume the update got lost and send it again
+            if client.sent_sequence == Some(self.sequence) && client.last_state_send.elapsed().num_milliseconds() < 1000 {
+                continue;
+            }
+
+            let baseline = client.acked_sequence.and_then(|acked_sequence| {
+                self.update_history.iter().find(|(sequence, _)| *sequence == acked_sequence)
+            });
+
+            let packet = match baseline {
+                Some((baseline_sequence, baseline_game_state)) => NetworkPacket::StateDiff {
+                    baseline: *baseline_sequence,
+                    sequence: self.sequence,
+                    diff: baseline_game_state.diff(&self.game_state)
+                },
+                // the client hasnt acknowledged anything or it was so long ago that we dont have it anymore
+                None => NetworkPacket::FullState {
+                    sequence: self.sequence,
+                    game_state: self.game_state.clone()
+                },
+            };
+
+            client.send(packet.to_bytes());
+
+            client.sent_sequence = Some(self.sequence);
+            client.last_state_send = Time::now();
+        }
+    }
+
+    pub fn remove_disconnected_clients(&mut self) {
+
+        for client in self.clients.iter().filter(|client| client.disconnected) {
+
+            info!(client = %client.name, "client disconnected");
+
+            // clients that never said hello never showed up in chat or the player list
+            if client.uuid.is_none() {
+                continue;
+            }
+
+            // whatever they had selected is free for the other editors now
+            if client.editor {
+                self.locks.retain(|_, holder| client.uuid.as_ref() != Some(holder));
+
+                self.collaborators_dirty = true;
+
+                continue;
+            }
+
+            self.pending_broadcasts.push(
+                NetworkPacket::ChatMessage(ChatMessage::system(format!("{} left the game", client.name)))
+            );
+
+            self.player_list_dirty = true;
+        }
+
+        self.clients.retain(|client| !client.disconnected);
+    }
+
+    // measure every client's ping once a second
+    pub fn ping_clients(&mut self) {
+
+        if self.last_ping.elapsed().num_milliseconds() < 1000 {
+            return;
+        }
+
+        let ping_bytes = NetworkPacket::Ping(chrono::Utc::now().timestamp_millis()).to_bytes();
+
+        for client in self.clients.iter_mut() {
+            client.send(ping_bytes.clone());
+        }
+
+        // the pings and square counts have probably changed since last time
+        self.player_list_dirty = true;
+
+        self.last_ping = Time::now();
+    }
+
+    // the number of clients taking up a player slot
+    pub fn player_count(&self) -> usize {
+        self.clients.iter().filter(|client| client.uuid.is_some() && !client.spectator && !client.editor).count()
+    }
+
+    pub fn player_list(&self) -> Vec<PlayerInfo> {
+
+        self.clients.iter().filter(|client| !client.editor).filter_map(|client| {
+
+            let uuid = client.uuid.clone()?;
+
+            let square_count = self.game_state.entities.values()
+                .filter(|entity| matches!(entity, Entity::PhysicsSquare(_)) && entity.get_owner() == uuid)
+                .count();
+
+            Some(
+                PlayerInfo {
+                    uuid,
+                    name: client.name.clone(),
+                    ping: client.ping,
+                    square_count,
+                    spectator: client.spectator
+                }
+            )
+        }).collect()
+    }
+
+    pub fn flush_broadcasts(&mut self) {
+
+        if self.player_list_dirty {
+            let player_list = self.player_list();
+
+            self.pending_broadcasts.push(NetworkPacket::PlayerList(player_list));
+
+            self.player_list_dirty = false;
+        }
+
+        // chat, sounds and the player list are only for people in the game
+        for packet in self.pending_broadcasts.drain(..) {
 
+            let packet_bytes = packet.to_bytes();
+
+            for client in self.clients.iter_mut().filter(|client| !client.editor) {
+                client.send(packet_bytes.clone());
+            }
+        }
+
+        if self.collaborators_dirty {
+
+            let packet_bytes = NetworkPacket::Collaborators {
+                collaborators: self.collaborators(),
+                locks: self.locks.clone()
+            }.to_bytes();
+
+            for client in self.clients.iter_mut().filter(|client| client.editor) {
+                client.send(packet_bytes.clone());
+            }
+
+            self.collaborators_dirty = false;
+        }
+    }
+
+    pub fn collaborators(&self) -> Vec<Collaborator> {
+
+        self.clients.iter().filter(|client| client.editor).filter_map(|client| {
+            Some(
+                Collaborator {
+                    uuid: client.uuid.clone()?,
+                    name: client.name.clone(),
+                    cursor: client.cursor.clone(),
+                    selected: client.selected.clone()
+                }
+            )
+        }).collect()
+    }
+
+    // an editor gets the lock on everything they select that nobody else got to first, and lets go of whatever they deselect.
+    // locks only change here so two editors selecting the same structure at once always agree on who got it
+    pub fn update_locks(&mut self, client: &ConnectedClient) {
+
+        let uuid = match &client.uuid {
+            Some(uuid) => uuid,
+            None => return,
+        };
+
+        self.locks.retain(|id, holder| holder != uuid || client.selected.contains(id));
+
+        for id in &client.selected {
+            if self.shared_level.structures.contains_key(id) {
+                self.locks.entry(id.clone()).or_insert(uuid.clone());
+            }
+        }
+    }
+
+    // locks are checked here as well as in the editors, since an editor's undo, physics step or opening a level
+    // can still touch structures someone else is holding. those structures are put back how they were.
+    // returns true if anything had to be put back
+    pub fn restore_locked_structures(&mut self, edited_level: &mut Level, uuid: &String) -> bool {
+
+        let mut restored = false;
+
+        for (id, holder) in &self.locks {
+
+            if holder == uuid {
+                continue;
+            }
+
+            let mut structure = match self.shared_level.structures.get(id) {
+                Some(structure) => structure.clone(),
+                None => continue,
+            };
+
+            // the shared level is built from clients' diffs so it cant be trusted to have a body for every structure
+            let rigid_body = match self.shared_level.space.get_rigid_body_mut(&structure.rigid_body_handle) {
+                Some(rigid_body) => rigid_body.clone(),
+                None => {
+                    warn!(%id, "locked structure has no rigid body, cant protect it");
+
+                    continue;
+                },
+            };
+
+            match edited_level.space.get_rigid_body_mut(&structure.rigid_body_handle) {
+                Some(edited_rigid_body) => {
+                    restored |= *edited_rigid_body != rigid_body;
+
+                    *edited_rigid_body = rigid_body;
+                },
+                // it was deleted so it goes back in, under a new handle
+                None => {
+                    restored = true;
+
+                    structure.rigid_body_handle = edited_level.space.insert_rigid_body(rigid_body);
+                },
+            }
+
+            restored |= edited_level.structures.get(id) != Some(&structure);
+
+            edited_level.structures.insert(id.clone(), structure);
+        }
+
+        restored
+    }
+
+    pub fn release_deleted_locks(&mut self) {
+
+        let lock_count = self.locks.len();
+
+        let structures = &self.shared_level.structures;
+
+        self.locks.retain(|id, _| structures.contains_key(id));
+
+        if self.locks.len() != lock_count {
+            self.collaborators_dirty = true;
+        }
+    }
+
+    // send every editor the changes to the shared level they dont have yet
+    pub fn send_level_updates(&mut self) {
+
+        for client in self.clients.iter_mut() {
+
+            let level_baseline = match &mut client.level_baseline {
+                Some(level_baseline) => level_baseline,
+                None => continue,
+            };
+
+            if *level_baseline == self.shared_level {
+                continue;
+            }
+
+            let diff = level_baseline.diff(&self.shared_level);
+
+            *level_baseline = self.shared_level.clone();
+
+            client.send(NetworkPacket::LevelDiff(diff).to_bytes());
+        }
+    }
+
+    // returns true if a client connected
+    pub fn accept_new_client(&mut self) -> Result<bool, NetError> {
+
+        let (stream, address) = match self.listener.accept() {
+            Ok(connection) => connection,
+            Err(error) if error.kind() == std::io::ErrorKind::WouldBlock => return Ok(false), // no new clients
+            Err(error) => return Err(error.into()),
+        };
+
+        info!(%address, "received new connection");
+
+        stream.set_nonblocking(true)?;
+
+        let mut handshake = tungstenite::accept(stream);
+
+        let websocket_stream = loop {
+            match handshake {
+                Ok(websocket_stream) => break websocket_stream,
+                // try again if the handshake isnt done yet
+                Err(tungstenite::HandshakeError::Interrupted(mid_handshake)) => handshake = mid_handshake.handshake(),
+                Err(tungstenite::HandshakeError::Failure(error)) => return Err(NetError::Handshake(error)),
+            };
+        };
+
+        // the client doesnt have any state we can diff against yet so send_state_updates will send them the full state
+
+        debug!(%address, "websocket handshake complete");
+
+        self.clients.push(ConnectedClient::new(websocket_stream));
+
+        Ok(true)
+    }
+}
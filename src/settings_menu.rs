@@ -0,0 +1,158 @@
+use macroquad::{color::{Color, WHITE, YELLOW}, input::{is_key_pressed, KeyCode}, shapes::draw_rectangle, text::draw_text, window::{screen_height, screen_width}};
+
+use crate::input::{Action, Input};
+
+const LINE_HEIGHT: f32 = 24.;
+// where the first action is drawn
+const LIST_Y: f32 = 120.;
+
+// lets the player rebind actions while the game is running. changes are saved to the bindings file straight away
+pub struct SettingsMenu {
+    pub open: bool,
+    // index into Action::GAME
+    pub selected: usize,
+    // which of the selected action's bindings gets replaced. one past the last adds a new binding
+    pub slot: usize,
+    // the first action shown, when there are more than fit on screen
+    pub scroll: usize,
+    // waiting for the player to press whatever they want the selected action bound to
+    pub rebinding: bool
+}
+
+impl SettingsMenu {
+
+    pub fn new() -> Self {
+        Self {
+            open: false,
+            selected: 0,
+            slot: 0,
+            scroll: 0,
+            rebinding: false,
+        }
+    }
+
+    // the menu reads raw keys instead of actions so a bad binding can never lock the player out of fixing it
+    pub fn update(&mut self, input: &mut Input) {
+
+        if !self.open {
+            if input.state.is_pressed(Action::Settings) {
+                self.open = true;
+                self.rebinding = false;
+            }
+
+            return;
+        }
+
+        if self.rebinding {
+
+            // escape cancels instead of being bound
+            if is_key_pressed(KeyCode::Escape) {
+                self.rebinding = false;
+
+                return;
+            }
+
+            if let Some(binding) = input.last_pressed_binding() {
+                input.rebind(Action::GAME[self.selected], self.slot, binding);
+
+                self.rebinding = false;
+            }
+
+            return;
+        }
+
+        if is_key_pressed(KeyCode::Escape) || input.state.is_pressed(Action::Settings) {
+            self.open = false;
+
+            return;
+        }
+
+        if is_key_pressed(KeyCode::Up) {
+            self.selected = (self.selected + Action::GAME.len() - 1) % Action::GAME.len();
+            self.slot = 0;
+        }
+
+        if is_key_pressed(KeyCode::Down) {
+            self.selected = (self.selected + 1) % Action::GAME.len();
+            self.slot = 0;
+        }
+
+        let slots = input.bindings.get(Action::GAME[self.selected]).len() + 1;
+
+        if is_key_pressed(KeyCode::Left) {
+            self.slot = (self.slot + slots - 1) % slots;
+        }
+
+        if is_key_pressed(KeyCode::Right) {
+            self.slot = (self.slot + 1) % slots;
+        }
+
+        // resetting can leave fewer bindings than before
+        self.slot = self.slot.min(slots - 1);
+
+        // keep the selected action on screen
+        let visible = visible_rows();
+
+        if self.selected < self.scroll {
+            self.scroll = self.selected;
+        }
+
+        if self.selected >= self.scroll + visible {
+            self.scroll = self.selected + 1 - visible;
+        }
+
+        if is_key_pressed(KeyCode::Enter) {
+            self.rebinding = true;
+        }
+
+        if is_key_pressed(KeyCode::Backspace) {
+            input.reset_binding(Action::GAME[self.selected]);
+
+            self.slot = 0;
+        }
+    }
+
+    pub fn draw(&self, input: &Input) {
+
+        if !self.open {
+            return;
+        }
+
+        draw_rectangle(0., 0., screen_width(), screen_height(), Color::new(0., 0., 0., 0.8));
+
+        draw_text("Controls", 40., 50., 40., WHITE);
+        draw_text("up/down to pick, left/right for which binding, enter to rebind, backspace to reset, escape to close", 40., 80., 20., WHITE);
+
+        for (index, action) in Action::GAME.iter().enumerate().skip(self.scroll).take(visible_rows()) {
+
+            let y = LIST_Y + LINE_HEIGHT * (index - self.scroll) as f32;
+
+            let mut slots: Vec<String> = input.bindings.get(*action).iter().map(|binding| binding.to_string()).collect();
+
+            // the empty slot at the end is where a new binding goes
+            if index == self.selected {
+                slots.push("+".to_string());
+
+                slots[self.slot] = match self.rebinding {
+                    true => "[press a key, button or stick...]".to_string(),
+                    false => format!("[{}]", slots[self.slot]),
+                };
+            }
+
+            let bindings = slots.join(", ");
+
+            let color = match index == self.selected {
+                true => YELLOW,
+                false => WHITE,
+            };
+
+            draw_text(&format!("{:?}", action), 40., y, 22., color);
+            draw_text(&bindings, 340., y, 22., color);
+        }
+    }
+}
+
+// how many actions fit between the top of the list and the bottom of the window
+fn visible_rows() -> usize {
+    (((screen_height() - LIST_Y) / LINE_HEIGHT) as usize).max(1)
+}
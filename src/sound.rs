@@ -0,0 +1,315 @@
+use std::collections::{HashMap, HashSet};
+
+use gamelibrary::proxies::macroquad::math::vec2::Vec2;
+use macroquad::audio::{play_sound, PlaySoundParams, Sound};
+use serde::{Deserialize, Serialize};
+use tracing::warn;
+
+pub const SPAWN_SOUND: &str = "assets/sounds/spawn.wav";
+pub const JUMP_SOUND: &str = "assets/sounds/jump.wav";
+pub const IMPACT_SOUND: &str = "assets/sounds/impact.wav";
+
+// sounds closer to the center of the screen than this play at full volume
+const FULL_VOLUME_DISTANCE: f32 = 300.;
+// and sounds further than this cant be heard at all
+const SILENT_DISTANCE: f32 = 1500.;
+// sounds this far to the side of the listener only come out of one speaker
+const FULL_PAN_DISTANCE: f32 = 800.;
+
+#[derive(Serialize, Deserialize, Clone, Copy, PartialEq)]
+pub enum SoundChannel {
+    Music,
+    Sfx
+}
+
+// a sound that plays once. these get sent to the server so everyone else hears them too
+#[derive(Serialize, Deserialize, Clone)]
+pub struct SoundEvent {
+    // the asset key of the sound
+    pub key: String,
+    // where in the world it came from. sounds without a position play at the same volume everywhere
+    pub position: Option<Vec2>,
+    pub channel: SoundChannel,
+    pub volume: f32,
+    // the uuid of the client that played it. the server fills this in so clients dont hear their own sounds twice
+    pub source: String
+}
+
+impl SoundEvent {
+    pub fn sfx(key: &str, position: Vec2, volume: f32) -> Self {
+        Self {
+            key: key.to_string(),
+            position: Some(position),
+            channel: SoundChannel::Sfx,
+            volume,
+            source: String::new(),
+        }
+    }
+}
+
+#[derive(Serialize, Deserialize, Clone, Copy)]
+pub struct SoundMix {
+    pub master: f32,
+    pub music: f32,
+    pub sfx: f32
+}
+
+impl Default for SoundMix {
+    fn default() -> Self {
+        Self {
+            master: 1.,
+            music: 0.5,
+            sfx: 1.,
+        }
+    }
+}
+
+impl SoundMix {
+    pub fn channel_volume(&self, channel: SoundChannel) -> f32 {
+        let channel_volume = match channel {
+            SoundChannel::Music => self.music,
+            SoundChannel::Sfx => self.sfx,
+        };
+
+        self.master * channel_volume
+    }
+}
+
+// macroquad cant set the stereo balance of a sound, so every sound is also loaded as a copy that only plays on the left
+// and one that only plays on the right. panning plays both copies at different volumes
+pub fn panned_key(key: &str, right: bool) -> String {
+    match right {
+        true => format!("{}#right", key),
+        false => format!("{}#left", key),
+    }
+}
+
+// the left and right only copies of a 16 bit pcm wav, as wav files. anything else returns None and just doesnt get panned
+pub fn split_channels(wav: &[u8]) -> Option<(Vec<u8>, Vec<u8>)> {
+
+    if wav.len() < 12 || &wav[0..4] != b"RIFF" || &wav[8..12] != b"WAVE" {
+        return None;
+    }
+
+    let mut format: Option<(u16, u16, u32, u16)> = None;
+    let mut data: Option<&[u8]> = None;
+
+    // chunks can come in any order and there can be extra ones like LIST that we skip
+    let mut offset = 12;
+
+    while offset + 8 <= wav.len() {
+
+        let id = &wav[offset..offset + 4];
+        let size = u32::from_le_bytes(wav[offset + 4..offset + 8].try_into().ok()?) as usize;
+
+        let body = wav.get(offset + 8..offset + 8 + size)?;
+
+        match id {
+            b"fmt " if size >= 16 => {
+                format = Some((
+                    u16::from_le_bytes([body[0], body[1]]),
+                    u16::from_le_bytes([body[2], body[3]]),
+                    u32::from_le_bytes([body[4], body[5], body[6], body[7]]),
+                    u16::from_le_bytes([body[14], body[15]])
+                ));
+            },
+            b"data" => data = Some(body),
+            _ => {},
+        }
+
+        // chunks are padded to an even length
+        offset += 8 + size + size % 2;
+    }
+
+    let (audio_format, channels, sample_rate, bits_per_sample) = format?;
+    let data = data?;
+
+    if audio_format != 1 || bits_per_sample != 16 || channels == 0 {
+        return None;
+    }
+
+    // mix every channel down to one so each side gets the whole sound
+    let mono: Vec<i16> = data.chunks_exact(2 * channels as usize).map(|frame| {
+
+        let sum: i32 = frame.chunks_exact(2).map(|sample| i16::from_le_bytes([sample[0], sample[1]]) as i32).sum();
+
+        (sum / channels as i32) as i16
+    }).collect();
+
+    let stereo = |right: bool| {
+
+        let data_size = mono.len() as u32 * 4;
+
+        let mut bytes = Vec::with_capacity(44 + data_size as usize);
+
+        bytes.extend_from_slice(b"RIFF");
+        bytes.extend_from_slice(&(36 + data_size).to_le_bytes());
+        bytes.extend_from_slice(b"WAVEfmt ");
+        bytes.extend_from_slice(&16u32.to_le_bytes());
+        bytes.extend_from_slice(&1u16.to_le_bytes());
+        bytes.extend_from_slice(&2u16.to_le_bytes());
+        bytes.extend_from_slice(&sample_rate.to_le_bytes());
+        bytes.extend_from_slice(&(sample_rate * 4).to_le_bytes());
+        bytes.extend_from_slice(&4u16.to_le_bytes());
+        bytes.extend_from_slice(&16u16.to_le_bytes());
+        bytes.extend_from_slice(b"data");
+        bytes.extend_from_slice(&data_size.to_le_bytes());
+
+        for sample in &mono {
+            let (left, right) = match right {
+                true => (0, *sample),
+                false => (*sample, 0),
+            };
+
+            bytes.extend_from_slice(&left.to_le_bytes());
+            bytes.extend_from_slice(&right.to_le_bytes());
+        }
+
+        bytes
+    };
+
+    Some((stereo(false), stereo(true)))
+}
+
+// how loud each speaker should be for a sound this far to the right of the listener (negative is to the left)
+pub fn pan(offset_x: f32) -> (f32, f32) {
+
+    let pan = (offset_x / FULL_PAN_DISTANCE).clamp(-1., 1.);
+
+    // in the middle both sides play at full volume, which sounds the same as the original
+    ((1. - pan).min(1.), (1. + pan).min(1.))
+}
+
+// how loud a sound at this distance from the listener should be, between 0 and 1
+pub fn attenuation(distance: f32) -> f32 {
+
+    if distance <= FULL_VOLUME_DISTANCE {
+        return 1.
+    }
+
+    (1. - (distance - FULL_VOLUME_DISTANCE) / (SILENT_DISTANCE - FULL_VOLUME_DISTANCE)).max(0.)
+}
+
+pub struct SoundPlayer {
+    pub mix: SoundMix,
+    // so we only complain once about each sound that isnt loaded
+    pub missing: HashSet<String>
+}
+
+impl SoundPlayer {
+
+    pub fn new(mix: SoundMix) -> Self {
+        Self {
+            mix,
+            missing: HashSet::new(),
+        }
+    }
+
+    // sounds get quieter with distance and pan towards the side they came from.
+    // the listener is the world position at the center of the screen
+    pub fn play(&mut self, event: &SoundEvent, sounds: &HashMap<String, Sound>, listener: &Vec2) {
+
+        let sound = match sounds.get(&event.key) {
+            Some(sound) => sound,
+            None => {
+                if self.missing.insert(event.key.clone()) {
+                    warn!(key = %event.key, "tried to play a sound that isnt loaded");
+                }
+
+                return;
+            },
+        };
+
+        let distance_volume = match &event.position {
+            Some(position) => {
+                attenuation(((position.x - listener.x).powi(2) + (position.y - listener.y).powi(2)).sqrt())
+            },
+            None => 1.,
+        };
+
+        let volume = event.volume * distance_volume * self.mix.channel_volume(event.channel);
+
+        if volume <= 0. {
+            return;
+        }
+
+        let panned = (sounds.get(&panned_key(&event.key, false)), sounds.get(&panned_key(&event.key, true)));
+
+        match (&event.position, panned) {
+            (Some(position), (Some(left), Some(right))) => {
+
+                let (left_volume, right_volume) = pan(position.x - listener.x);
+
+                play_sound(left, PlaySoundParams { looped: false, volume: volume * left_volume });
+                play_sound(right, PlaySoundParams { looped: false, volume: volume * right_volume });
+            },
+            // sounds that arent 16 bit wavs dont have panned copies
+            _ => play_sound(sound, PlaySoundParams { looped: false, volume }),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // a 16 bit stereo wav with an extra chunk before the data, like the ones ffmpeg writes
+    fn stereo_wav(frames: &[(i16, i16)]) -> Vec<u8> {
+
+        let mut data = vec![];
+
+        for (left, right) in frames {
+            data.extend_from_slice(&left.to_le_bytes());
+            data.extend_from_slice(&right.to_le_bytes());
+        }
+
+        let mut bytes = vec![];
+
+        bytes.extend_from_slice(b"RIFF");
+        bytes.extend_from_slice(&(4 + 24 + 14 + 8 + data.len() as u32).to_le_bytes());
+        bytes.extend_from_slice(b"WAVEfmt ");
+        bytes.extend_from_slice(&16u32.to_le_bytes());
+        bytes.extend_from_slice(&1u16.to_le_bytes());
+        bytes.extend_from_slice(&2u16.to_le_bytes());
+        bytes.extend_from_slice(&48000u32.to_le_bytes());
+        bytes.extend_from_slice(&(48000u32 * 4).to_le_bytes());
+        bytes.extend_from_slice(&4u16.to_le_bytes());
+        bytes.extend_from_slice(&16u16.to_le_bytes());
+        // odd sized so the padding byte gets skipped
+        bytes.extend_from_slice(b"LIST");
+        bytes.extend_from_slice(&5u32.to_le_bytes());
+        bytes.extend_from_slice(b"INFO\0\0");
+        bytes.extend_from_slice(b"data");
+        bytes.extend_from_slice(&(data.len() as u32).to_le_bytes());
+        bytes.extend_from_slice(&data);
+
+        bytes
+    }
+
+    fn samples(wav: &[u8]) -> Vec<(i16, i16)> {
+        wav[44..].chunks_exact(4).map(|frame| {
+            (i16::from_le_bytes([frame[0], frame[1]]), i16::from_le_bytes([frame[2], frame[3]]))
+        }).collect()
+    }
+
+    #[test]
+    fn split_channels_mixes_down_and_silences_one_side() {
+
+        let (left, right) = split_channels(&stereo_wav(&[(100, 300), (-50, -150)])).unwrap();
+
+        assert_eq!(samples(&left), vec![(200, 0), (-100, 0)]);
+        assert_eq!(samples(&right), vec![(0, 200), (0, -100)]);
+    }
+
+    #[test]
+    fn split_channels_ignores_other_formats() {
+        assert!(split_channels(b"OggS not a wav at all").is_none());
+    }
+
+    #[test]
+    fn pan_is_centered_and_clamped() {
+        assert_eq!(pan(0.), (1., 1.));
+        assert_eq!(pan(FULL_PAN_DISTANCE * 2.), (0., 1.));
+        assert_eq!(pan(-FULL_PAN_DISTANCE / 2.), (1., 0.5));
+    }
+}
@@ -1,37 +1,198 @@
+use std::collections::HashMap;
+
 use diff::Diff;
-use gamelibrary::{menu::Menu, proxies::macroquad::{color::colors::{DARKGRAY, GREEN, RED}, math::vec2::Vec2}, space::{RigidBodyHandle, Space}, traits::{Color, HasRigidBody}, translate_coordinates};
-use macroquad::input::{self, is_key_down, is_mouse_button_released, mouse_position};
+use gamelibrary::{menu::Menu, proxies::macroquad::{color::colors::{DARKGRAY, GREEN, RED}, math::vec2::Vec2}, rigid_body::RigidBodyType, space::{RigidBodyHandle, Space}, traits::{Color, HasOwner, HasRigidBody}};
+use macroquad::{color::WHITE, shapes::{draw_rectangle, draw_triangle}, texture::{draw_texture_ex, DrawTextureParams, Texture2D}, window::screen_height};
 use serde::{Serialize, Deserialize};
 
-use crate::level::Level;
+use crate::{camera::Camera, input::{Action, InputState}, level::Level, TickContext};
+
+// how big the resize handles on the corners of a selected structure are, in pixels
+pub const RESIZE_HANDLE_SIZE: f32 = 10.;
+// structures cant be resized smaller than this half extent
+pub const MIN_HALF_EXTENT: f32 = 2.;
+// how quickly friction slows things down. at a friction of 1 sliding speed drops by this much of itself every second, roughly
+const FRICTION_STRENGTH: f32 = 10.;
+// how many straight edges a circle is drawn and picked with. capsule ends get half as many each
+const CIRCLE_SEGMENTS: usize = 32;
+
+// the outline a structure is drawn and picked with. gamelibrary colliders are boxes, so every shape is fitted to the collider's
+// half extents and physics uses that box. resizing a structure resizes its shape along with the collider
+#[derive(Serialize, Deserialize, Diff, PartialEq, Clone, Default)]
+#[diff(attr(
+    #[derive(Serialize, Deserialize)]
+))]
+pub enum Shape {
+    #[default]
+    Rectangle,
+    // the biggest circle that fits in the box
+    Circle,
+    // rounded off at both ends of its longer side
+    Capsule,
+    // convex and counterclockwise. points go from -1 to 1 and get scaled by the half extents
+    Polygon(Vec<Vec2>)
+}
+
+impl Shape {
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            Shape::Rectangle => "rectangle",
+            Shape::Circle => "circle",
+            Shape::Capsule => "capsule",
+            Shape::Polygon(_) => "polygon",
+        }
+    }
+
+    // counterclockwise around the center, in the structure's own unrotated frame
+    pub fn outline(&self, hx: f32, hy: f32) -> Vec<Vec2> {
+        match self {
+            Shape::Rectangle => vec![Vec2::new(-hx, -hy), Vec2::new(hx, -hy), Vec2::new(hx, hy), Vec2::new(-hx, hy)],
+            Shape::Circle => arc(&Vec2::new(0., 0.), hx.min(hy), 0., std::f32::consts::TAU, CIRCLE_SEGMENTS),
+            Shape::Capsule => {
+
+                let radius = hx.min(hy);
+
+                // the caps go on the ends of the longer side
+                let (first, second, start) = match hx >= hy {
+                    true => (Vec2::new(hx - radius, 0.), Vec2::new(radius - hx, 0.), -std::f32::consts::FRAC_PI_2),
+                    false => (Vec2::new(0., hy - radius), Vec2::new(0., radius - hy), 0.),
+                };
+
+                let mut outline = arc(&first, radius, start, std::f32::consts::PI, CIRCLE_SEGMENTS / 2);
+
+                outline.extend(arc(&second, radius, start + std::f32::consts::PI, std::f32::consts::PI, CIRCLE_SEGMENTS / 2));
+
+                outline
+            },
+            Shape::Polygon(points) => points.iter().map(|point| Vec2::new(point.x * hx, point.y * hy)).collect(),
+        }
+    }
+
+    // local is in the structure's own unrotated frame. every shape is convex so the point just has to be left of every edge
+    pub fn contains(&self, hx: f32, hy: f32, local: &Vec2) -> bool {
+
+        let outline = self.outline(hx, hy);
+
+        (0..outline.len()).all(|index| {
+
+            let (start, end) = (&outline[index], &outline[(index + 1) % outline.len()]);
+
+            (end.x - start.x) * (local.y - start.y) - (end.y - start.y) * (local.x - start.x) >= -0.001
+        })
+    }
+}
+
+// points around part of a circle, including both ends
+fn arc(center: &Vec2, radius: f32, start: f32, sweep: f32, segments: usize) -> Vec<Vec2> {
+
+    // a full circle would repeat its first point at the end
+    let points = match sweep >= std::f32::consts::TAU {
+        true => segments,
+        false => segments + 1,
+    };
+
+    (0..points).map(|index| {
+
+        let angle = start + sweep * index as f32 / segments as f32;
+
+        Vec2::new(center.x + radius * angle.cos(), center.y + radius * angle.sin())
+    }).collect()
+}
+
+// the convex hull of some points, counterclockwise and without any points in the middle of an edge
+pub fn convex_hull(points: &[Vec2]) -> Vec<Vec2> {
+
+    let mut points: Vec<Vec2> = points.to_vec();
+
+    points.sort_by(|a, b| a.x.total_cmp(&b.x).then(a.y.total_cmp(&b.y)));
+
+    if points.len() < 3 {
+        return points;
+    }
+
+    let cross = |o: &Vec2, a: &Vec2, b: &Vec2| (a.x - o.x) * (b.y - o.y) - (a.y - o.y) * (b.x - o.x);
+
+    let mut hull: Vec<Vec2> = vec![];
+
+    // the bottom half going right then the top half coming back
+    for pass in [points.clone(), points.iter().rev().cloned().collect()] {
+
+        let start = hull.len();
+
+        for point in pass {
+            while hull.len() >= start + 2 && cross(&hull[hull.len() - 2], &hull[hull.len() - 1], &point) <= 0. {
+                hull.pop();
+            }
+
+            hull.push(point);
+        }
+
+        // the last point of each half is the first of the other
+        hull.pop();
+    }
+
+    hull
+}
 
 #[derive(Serialize, serde::Deserialize, Diff, PartialEq, Clone)]
 #[diff(attr(
     #[derive(Serialize, Deserialize)]
 ))]
 pub struct Structure {
+    // whoever simulates the structure's rigid body. in the editor this is always "host".
+    // older level files dont have it, which is fine because loading a level into a game hands everything to a new owner anyway
+    #[serde(default)]
+    pub owner: String,
     pub rigid_body_handle: RigidBodyHandle,
     pub color: gamelibrary::proxies::macroquad::color::Color,
     pub menu: Option<Menu>,
     pub selected: bool,
     pub dragging: bool,
-    pub drag_offset: Option<Vec2>
+    pub drag_offset: Option<Vec2>,
+    // the corner that stays put while the opposite corner's handle is being dragged
+    #[serde(default)]
+    pub resize_anchor: Option<Vec2>,
+    // asset key of a texture drawn over the structure instead of its color, like "assets/structures/crate.png"
+    #[serde(default)]
+    pub texture: Option<String>,
+    // free form labels for finding structures from game code, like "spawn" or "breakable"
+    #[serde(default)]
+    pub tags: Vec<String>,
+    #[serde(default)]
+    pub shape: Shape,
+    // how much this slows down whatever slides across the top of it, from 0 for ice up to 1
+    #[serde(default)]
+    pub friction: f32,
+    // moves at its velocity no matter what hits it. the space only has dynamic and fixed bodies,
+    // so the body is fixed there to keep collisions from pushing it, and whoever owns it moves it
+    #[serde(default)]
+    pub kinematic: bool
 }
 
 impl Structure {
 
-    pub fn spawn_menu(&mut self, space: &mut Space) {
+    // resets everything that only matters while the structure is being edited
+    pub fn clear_editor_state(&mut self) {
+        self.menu = None;
+        self.selected = false;
+        self.dragging = false;
+        self.drag_offset = None;
+        self.resize_anchor = None;
+    }
+
+    pub fn spawn_menu(&mut self, space: &mut Space, input: &InputState, camera: &Camera) {
         
-        if !is_mouse_button_released(input::MouseButton::Right) {
+        if !input.is_released(Action::OpenStructureMenu) {
             return;
         }
 
-        let mouse_pos = Vec2::new(mouse_position().0, mouse_position().1);
+        let mouse_pos = input.mouse_position.clone();
 
         // this should probaby be cached somewhere
-        let intersections = space.query_point(translate_coordinates(&mouse_pos));
+        let intersections = space.query_point(camera.screen_to_world(&mouse_pos));
 
-        if !intersections.contains(self.get_rigid_body_handle()) {
+        if !intersections.contains(self.get_rigid_body_handle()) || !self.contains(space, &camera.screen_to_world(&mouse_pos)) {
             return
         }
 
@@ -46,36 +207,210 @@ impl Structure {
         self.menu = Some(menu);
     }
 
-    pub fn tick_editor(&mut self, level: &mut Level) {
+    // grid is the grid spacing when snapping to it is turned on
+    pub fn tick_editor(&mut self, level: &mut Level, input: &InputState, camera: &Camera, grid: Option<f32>) {
 
         match &mut self.menu {
             Some(menu) => menu.update(),
             None => {}
         }
 
-        self.spawn_menu(&mut level.space);
+        self.spawn_menu(&mut level.space, input, camera);
 
-        self.update_selected(&mut level.space);
+        // selecting, moving and rotating are done by the editor so they can work on a group of structures at once
+        self.resize(&mut level.space, input, camera, grid);
 
-        self.update_is_dragging(&mut level.space);
+    }
+
+    // the world positions of the four corners, taking rotation into account
+    pub fn corners(&self, space: &mut Space) -> [Vec2; 4] {
+
+        let rigid_body = space.get_rigid_body_mut(self.get_rigid_body_handle()).unwrap();
 
-        self.update_drag(&mut level.space);
+        let (hx, hy) = (rigid_body.collider.hx, rigid_body.collider.hy);
 
-        self.rotate(&mut level.space);
+        [(-1., -1.), (1., -1.), (1., 1.), (-1., 1.)].map(|(sign_x, sign_y)| {
 
+            let corner = rotate_vector(&Vec2::new(sign_x * hx, sign_y * hy), rigid_body.rotation);
 
+            Vec2::new(rigid_body.position.x + corner.x, rigid_body.position.y + corner.y)
+        })
     }
 
-    pub fn resize(&mut self) {
-        if !*self.get_selected() {return}
+    pub fn tick(&mut self, ctx: &mut TickContext) {
+        self.move_kinematic(&mut ctx.game_state.space, ctx.time.elapsed().num_milliseconds() as f32 / 1000.);
+    }
+
+    pub fn move_kinematic(&self, space: &mut Space, delta: f32) {
+
+        if !self.kinematic {
+            return;
+        }
+
+        if let Some(rigid_body) = space.get_rigid_body_mut(self.get_rigid_body_handle()) {
+            rigid_body.position.x += rigid_body.velocity.x * delta;
+            rigid_body.position.y += rigid_body.velocity.y * delta;
+            rigid_body.rotation += rigid_body.angular_velocity * delta;
+        }
+    }
+
+    // the world positions around the edge of the shape, counterclockwise
+    pub fn outline(&self, space: &mut Space) -> Vec<Vec2> {
+
+        let rigid_body = space.get_rigid_body_mut(self.get_rigid_body_handle()).unwrap();
+
+        self.shape.outline(rigid_body.collider.hx, rigid_body.collider.hy).iter().map(|point| {
+
+            let point = rotate_vector(point, rigid_body.rotation);
+
+            Vec2::new(rigid_body.position.x + point.x, rigid_body.position.y + point.y)
+        }).collect()
+    }
+
+    // whether a world position is inside the shape itself rather than just the collider's box around it
+    pub fn contains(&self, space: &mut Space, position: &Vec2) -> bool {
+
+        let rigid_body = match space.get_rigid_body_mut(self.get_rigid_body_handle()) {
+            Some(rigid_body) => rigid_body,
+            None => return false,
+        };
+
+        let local = rotate_vector(&Vec2::new(position.x - rigid_body.position.x, position.y - rigid_body.position.y), -rigid_body.rotation);
+
+        self.shape.contains(rigid_body.collider.hx, rigid_body.collider.hy, &local)
+    }
+
+    // dragging a corner handle moves that corner while the opposite one stays where it is
+    pub fn resize(&mut self, space: &mut Space, input: &InputState, camera: &Camera, grid: Option<f32>) {
+        if !*self.get_selected() {
+            self.resize_anchor = None;
+
+            return
+        }
+
+        let mouse_pos = camera.screen_to_world(&input.mouse_position);
+
+        if input.is_pressed(Action::ResizeSelected) {
+
+            let corners = self.corners(space);
+
+            // handles stay the same size on screen however far the camera is zoomed.
+            // they are drawn centered on the corner so the grab area only goes half their size out each way
+            let half_handle_size = RESIZE_HANDLE_SIZE / camera.zoom / 2.;
+
+            let grabbed = corners.iter().position(|corner| {
+                (corner.x - mouse_pos.x).abs() <= half_handle_size && (corner.y - mouse_pos.y).abs() <= half_handle_size
+            });
+
+            // corners are listed around the box so the opposite one is two along
+            if let Some(index) = grabbed {
+                self.resize_anchor = Some(corners[(index + 2) % 4].clone());
+            }
+        }
+
+        if !input.is_down(Action::ResizeSelected) {
+            self.resize_anchor = None;
+        }
+
+        let anchor = match &self.resize_anchor {
+            Some(anchor) => anchor.clone(),
+            None => return,
+        };
+
+        // the dragged corner lands on the grid so sizes come out as whole grid steps
+        let mouse_pos = match grid {
+            Some(size) => Vec2::new((mouse_pos.x / size).round() * size, (mouse_pos.y / size).round() * size),
+            None => mouse_pos,
+        };
+
+        let rigid_body = space.get_rigid_body_mut(self.get_rigid_body_handle()).unwrap();
+
+        // work out the size in the structure's own rotated frame so rotated boxes resize along their own sides
+        let local = rotate_vector(&Vec2::new(mouse_pos.x - anchor.x, mouse_pos.y - anchor.y), -rigid_body.rotation);
+
+        let mut hx = (local.x.abs() / 2.).max(MIN_HALF_EXTENT);
+        let mut hy = (local.y.abs() / 2.).max(MIN_HALF_EXTENT);
+
+        // circles keep a square box, otherwise the collider would stick out past the circle on the long side
+        if self.shape == Shape::Circle {
+            hx = hx.max(hy);
+            hy = hx;
+        }
+
+        // put the center back between the anchor and the clamped size
+        let center = rotate_vector(&Vec2::new(hx * local.x.signum(), hy * local.y.signum()), rigid_body.rotation);
+
+        rigid_body.position = Vec2::new(anchor.x + center.x, anchor.y + center.y);
+        rigid_body.collider.hx = hx;
+        rigid_body.collider.hy = hy;
     }
 
-    pub fn rotate(&mut self, space: &mut Space) {
+    // draws the texture if the structure has one that is loaded, otherwise falls back to the plain colored box
+    pub async fn draw_textured(&mut self, camera_offset: &Vec2, space: &mut Space, textures: &HashMap<String, Texture2D>) {
+
+        let texture = match self.texture.as_ref().and_then(|texture| textures.get(texture)) {
+            Some(texture) => texture,
+            None if self.shape == Shape::Rectangle => {
+                self.draw(camera_offset, space).await;
+
+                return;
+            },
+            None => {
+                self.draw_shape(camera_offset, space);
+
+                return;
+            },
+        };
+
+        let rigid_body = space.get_rigid_body_mut(self.get_rigid_body_handle()).unwrap();
+
+        draw_texture_ex(
+            texture,
+            rigid_body.position.x - camera_offset.x - rigid_body.collider.hx,
+            screen_height() - (rigid_body.position.y - camera_offset.y) - rigid_body.collider.hy,
+            WHITE,
+            DrawTextureParams {
+                dest_size: Some(macroquad::math::Vec2::new(rigid_body.collider.hx * 2., rigid_body.collider.hy * 2.)),
+                // screen y goes down so the rotation goes the other way
+                rotation: -rigid_body.rotation,
+                ..Default::default()
+            }
+        );
+    }
+
+    // every shape is convex so it can be filled as a fan of triangles from its center
+    pub fn draw_shape(&self, camera_offset: &Vec2, space: &mut Space) {
+
+        let outline = self.outline(space);
+
+        let center = space.get_rigid_body_mut(self.get_rigid_body_handle()).unwrap().position.clone();
+
+        let to_screen = |point: &Vec2| macroquad::math::Vec2::new(point.x - camera_offset.x, screen_height() - (point.y - camera_offset.y));
+
+        let color = macroquad::color::Color::new(self.color.r, self.color.g, self.color.b, self.color.a);
+
+        for index in 0..outline.len() {
+            draw_triangle(to_screen(&center), to_screen(&outline[index]), to_screen(&outline[(index + 1) % outline.len()]), color);
+        }
+    }
+
+    // draw with the camera set since the handles are positioned in the world
+    pub fn draw_resize_handles(&mut self, camera: &Camera, space: &mut Space) {
         if !*self.get_selected() {return}
 
-        if !is_key_down(input::KeyCode::R) {return}
+        let camera_offset = camera.offset();
+
+        let handle_size = RESIZE_HANDLE_SIZE / camera.zoom;
 
-        space.get_rigid_body_mut(self.get_rigid_body_handle()).unwrap().rotation -= 0.05
+        for corner in self.corners(space) {
+            draw_rectangle(
+                corner.x - camera_offset.x - handle_size / 2.,
+                screen_height() - (corner.y - camera_offset.y) - handle_size / 2.,
+                handle_size,
+                handle_size,
+                WHITE
+            );
+        }
     }
 
     pub fn handle_menu(mut self, space: &mut Space) -> Option<Self> {
@@ -94,7 +429,9 @@ impl Structure {
 
             match menu_item.text.as_str() {
                 "Delete" => {
-                    self.menu = None;
+                    // otherwise the body stays in the space as an invisible collider
+                    space.remove_rigid_body(&self.rigid_body_handle);
+
                     return None
                 },
                 "Zero Velocity" => {
@@ -116,6 +453,41 @@ impl Structure {
         Some(self)
     }
 }
+
+// slows down a dynamic body sliding across the top of a structure with friction. only the owner of the body should do this.
+// with gravity pointing down the top is the only side things rest on, so the sides and bottom are left alone
+pub fn apply_friction<'a>(space: &mut Space, rigid_body_handle: &RigidBodyHandle, structures: impl Iterator<Item = &'a Structure>, delta: f32) {
+
+    let probe = match space.get_rigid_body_mut(rigid_body_handle) {
+        Some(rigid_body) if matches!(rigid_body.body_type, RigidBodyType::Dynamic) => {
+            Vec2::new(rigid_body.position.x, rigid_body.position.y - rigid_body.collider.hy - 2.)
+        },
+        _ => return,
+    };
+
+    let below = space.query_point(probe);
+
+    let friction = structures
+        .filter(|structure| structure.rigid_body_handle != *rigid_body_handle && below.contains(&structure.rigid_body_handle))
+        .map(|structure| structure.friction)
+        .fold(0., f32::max);
+
+    if friction <= 0. {
+        return;
+    }
+
+    if let Some(rigid_body) = space.get_rigid_body_mut(rigid_body_handle) {
+        rigid_body.velocity.x *= (-friction.min(1.) * FRICTION_STRENGTH * delta).exp();
+    }
+}
+
+pub fn rotate_vector(vector: &Vec2, angle: f32) -> Vec2 {
+    Vec2::new(
+        vector.x * angle.cos() - vector.y * angle.sin(),
+        vector.x * angle.sin() + vector.y * angle.cos()
+    )
+}
+
 impl HasRigidBody for Structure {
     fn get_rigid_body_handle(&self) -> &RigidBodyHandle {
         &self.rigid_body_handle
@@ -138,4 +510,144 @@ impl Color for Structure {
     fn color(&mut self) -> &mut gamelibrary::proxies::macroquad::color::Color {
         &mut self.color
     }
-}
\ No newline at end of file
+}
+
+impl HasOwner for Structure {
+    fn get_owner(&self) -> String {
+        self.owner.clone()
+    }
+
+    fn set_owner(&mut self, uuid: String) {
+        self.owner = uuid
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use gamelibrary::{collider::Collider, rigid_body::RigidBody};
+
+    use super::*;
+
+    fn insert(space: &mut Space, position: Vec2, velocity: Vec2, hx: f32, hy: f32, body_type: RigidBodyType) -> Structure {
+
+        let rigid_body_handle = space.insert_rigid_body(RigidBody {
+            position,
+            rotation: 0.,
+            angular_velocity: 0.,
+            velocity,
+            body_type,
+            owner: "host".to_string(),
+            collider: Collider { hx, hy, restitution: 0., mass: 10., owner: "host".to_string() }
+        });
+
+        Structure {
+            owner: "host".to_string(),
+            rigid_body_handle,
+            color: RED,
+            menu: None,
+            selected: false,
+            dragging: false,
+            drag_offset: None,
+            resize_anchor: None,
+            texture: None,
+            tags: vec![],
+            shape: Shape::Rectangle,
+            friction: 0.,
+            kinematic: false
+        }
+    }
+
+    #[test]
+    fn friction_slows_things_sliding_across_the_top() {
+
+        let mut space = Space::new(0.);
+
+        let mut floor = insert(&mut space, Vec2::new(0., 0.), Vec2::ZERO, 100., 10., RigidBodyType::Fixed);
+        let crate_structure = insert(&mut space, Vec2::new(0., 20.), Vec2::new(100., 0.), 10., 10., RigidBodyType::Dynamic);
+
+        // no friction leaves it sliding
+        apply_friction(&mut space, &crate_structure.rigid_body_handle, [&floor, &crate_structure].into_iter(), 0.1);
+
+        assert_eq!(space.get_rigid_body_mut(&crate_structure.rigid_body_handle).unwrap().velocity.x, 100.);
+
+        floor.friction = 1.;
+
+        apply_friction(&mut space, &crate_structure.rigid_body_handle, [&floor, &crate_structure].into_iter(), 0.1);
+
+        let velocity = space.get_rigid_body_mut(&crate_structure.rigid_body_handle).unwrap().velocity.x;
+
+        assert!(velocity > 0. && velocity < 50., "{}", velocity);
+    }
+
+    #[test]
+    fn kinematic_structures_move_at_their_velocity() {
+
+        let mut space = Space::new(0.);
+
+        let mut platform = insert(&mut space, Vec2::new(0., 0.), Vec2::new(30., -10.), 50., 5., RigidBodyType::Fixed);
+
+        // not kinematic, so it stays put
+        platform.move_kinematic(&mut space, 0.5);
+
+        assert_eq!(space.get_rigid_body_mut(&platform.rigid_body_handle).unwrap().position.x, 0.);
+
+        platform.kinematic = true;
+
+        platform.move_kinematic(&mut space, 0.5);
+
+        let position = space.get_rigid_body_mut(&platform.rigid_body_handle).unwrap().position.clone();
+
+        assert_eq!((position.x, position.y), (15., -5.));
+    }
+
+    #[test]
+    fn circles_dont_reach_the_corners_of_their_box() {
+
+        assert!(Shape::Circle.contains(20., 20., &Vec2::new(0., 19.)));
+        assert!(!Shape::Circle.contains(20., 20., &Vec2::new(18., 18.)));
+
+        assert!(Shape::Rectangle.contains(20., 20., &Vec2::new(18., 18.)));
+    }
+
+    #[test]
+    fn capsules_round_off_their_longer_side() {
+
+        // 80 wide and 20 tall, so the ends are half circles with a radius of 10
+        assert!(Shape::Capsule.contains(40., 10., &Vec2::new(0., 9.)));
+        assert!(Shape::Capsule.contains(40., 10., &Vec2::new(39., 0.)));
+        assert!(!Shape::Capsule.contains(40., 10., &Vec2::new(39., 9.)));
+
+        // standing up the ends go on top and bottom instead
+        assert!(Shape::Capsule.contains(10., 40., &Vec2::new(0., 39.)));
+        assert!(!Shape::Capsule.contains(10., 40., &Vec2::new(9., 39.)));
+    }
+
+    #[test]
+    fn polygons_scale_with_the_collider() {
+
+        let triangle = Shape::Polygon(vec![Vec2::new(-1., -1.), Vec2::new(1., -1.), Vec2::new(-1., 1.)]);
+
+        assert!(triangle.contains(10., 10., &Vec2::new(-5., -5.)));
+        assert!(!triangle.contains(10., 10., &Vec2::new(5., 5.)));
+
+        assert!(triangle.contains(100., 10., &Vec2::new(-50., 0.)));
+    }
+
+    #[test]
+    fn convex_hull_drops_inside_and_in_line_points() {
+
+        let hull = convex_hull(&[
+            Vec2::new(0., 0.),
+            Vec2::new(10., 10.),
+            Vec2::new(20., 0.),
+            Vec2::new(10., 0.),
+            Vec2::new(10., 3.),
+            Vec2::new(0., 20.),
+        ]);
+
+        let hull: Vec<(f32, f32)> = hull.iter().map(|point| (point.x, point.y)).collect();
+
+        // 10, 10 is on the edge between 20, 0 and 0, 20
+        assert_eq!(hull, vec![(0., 0.), (20., 0.), (0., 20.)]);
+    }
+}
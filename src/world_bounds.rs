@@ -0,0 +1,290 @@
+use diff::Diff;
+use gamelibrary::{proxies::macroquad::math::vec2::Vec2, rigid_body::RigidBody};
+use serde::{Deserialize, Serialize};
+
+// what happens to a body that reaches one side of the world
+#[derive(Serialize, Deserialize, Diff, PartialEq, Clone, Copy)]
+#[diff(attr(
+    #[derive(Serialize, Deserialize)]
+))]
+pub enum EdgeBehavior {
+    // nothing stops it
+    Open,
+    // it stops the body, bouncing it back if the body has any restitution
+    Wall,
+    // it gets sent back to the spawn point. the body stays in the space because players and squares keep their handle for the whole game
+    Kill,
+    // it comes out the opposite side
+    Wrap
+}
+
+// the edges of the world. this is part of the game state so every peer enforces the same bounds no matter how big their window is
+#[derive(Serialize, Deserialize, Diff, PartialEq, Clone)]
+#[diff(attr(
+    #[derive(Serialize, Deserialize)]
+))]
+pub struct WorldBounds {
+    // bottom left corner
+    pub min: Vec2,
+    // top right corner
+    pub max: Vec2,
+    pub left: EdgeBehavior,
+    pub right: EdgeBehavior,
+    pub bottom: EdgeBehavior,
+    pub top: EdgeBehavior,
+    // where killed bodies go
+    pub spawn_point: Vec2
+}
+
+impl Default for WorldBounds {
+    // walls around the area the default window used to show. squares used to bounce off the window edges at full speed,
+    // walls only bounce them as much as their restitution, so with the default of 0 they stop dead instead
+    fn default() -> Self {
+        Self {
+            min: Vec2::new(0., 0.),
+            max: Vec2::new(1280., 720.),
+            left: EdgeBehavior::Wall,
+            right: EdgeBehavior::Wall,
+            bottom: EdgeBehavior::Wall,
+            top: EdgeBehavior::Wall,
+            spawn_point: Vec2::new(640., 500.),
+        }
+    }
+}
+
+impl WorldBounds {
+
+    pub fn width(&self) -> f32 {
+        self.max.x - self.min.x
+    }
+
+    pub fn height(&self) -> f32 {
+        self.max.y - self.min.y
+    }
+
+    // the same edges moved to fit around an area, like the structures in a level. the spawn point goes to the top middle
+    // if it would end up outside, so killed bodies dont land somewhere they get killed again
+    pub fn around(&self, min: &Vec2, max: &Vec2, padding: f32) -> Self {
+
+        let min = Vec2::new(min.x - padding, min.y - padding);
+        let max = Vec2::new(max.x + padding, max.y + padding);
+
+        let inside = (min.x..=max.x).contains(&self.spawn_point.x) && (min.y..=max.y).contains(&self.spawn_point.y);
+
+        let spawn_point = match inside {
+            true => self.spawn_point.clone(),
+            false => Vec2::new((min.x + max.x) / 2., max.y - padding / 2.),
+        };
+
+        Self {
+            min,
+            max,
+            spawn_point,
+            ..self.clone()
+        }
+    }
+
+    // the area the camera should stay inside, if the world actually ends somewhere
+    pub fn camera_bounds(&self) -> Option<(Vec2, Vec2)> {
+
+        let edges = [self.left, self.right, self.bottom, self.top];
+
+        if edges.contains(&EdgeBehavior::Open) {
+            return None
+        }
+
+        Some((self.min.clone(), self.max.clone()))
+    }
+
+    pub fn enforce(&self, rigid_body: &mut RigidBody) {
+
+        let hx = rigid_body.collider.hx;
+        let hy = rigid_body.collider.hy;
+        // walls are as bouncy as whatever hits them
+        let restitution = rigid_body.collider.restitution;
+
+        // walls stop the edge of the body but everything else waits for the center to cross, otherwise wrapping would bounce straight back
+        let past_left = |behavior: EdgeBehavior, x: f32| match behavior {
+            EdgeBehavior::Wall => x - hx < self.min.x,
+            _ => x < self.min.x,
+        };
+
+        let past_right = |behavior: EdgeBehavior, x: f32| match behavior {
+            EdgeBehavior::Wall => x + hx > self.max.x,
+            _ => x > self.max.x,
+        };
+
+        let past_bottom = |behavior: EdgeBehavior, y: f32| match behavior {
+            EdgeBehavior::Wall => y - hy < self.min.y,
+            _ => y < self.min.y,
+        };
+
+        let past_top = |behavior: EdgeBehavior, y: f32| match behavior {
+            EdgeBehavior::Wall => y + hy > self.max.y,
+            _ => y > self.max.y,
+        };
+
+        let mut killed = false;
+
+        if past_left(self.left, rigid_body.position.x) {
+            match self.left {
+                EdgeBehavior::Open => {},
+                EdgeBehavior::Wall => {
+                    rigid_body.position.x = self.min.x + hx;
+                    rigid_body.velocity.x = rigid_body.velocity.x.abs() * restitution;
+                },
+                EdgeBehavior::Kill => killed = true,
+                EdgeBehavior::Wrap => rigid_body.position.x += self.width(),
+            }
+        } else if past_right(self.right, rigid_body.position.x) {
+            match self.right {
+                EdgeBehavior::Open => {},
+                EdgeBehavior::Wall => {
+                    rigid_body.position.x = self.max.x - hx;
+                    rigid_body.velocity.x = -rigid_body.velocity.x.abs() * restitution;
+                },
+                EdgeBehavior::Kill => killed = true,
+                EdgeBehavior::Wrap => rigid_body.position.x -= self.width(),
+            }
+        }
+
+        if past_bottom(self.bottom, rigid_body.position.y) {
+            match self.bottom {
+                EdgeBehavior::Open => {},
+                EdgeBehavior::Wall => {
+                    rigid_body.position.y = self.min.y + hy;
+                    rigid_body.velocity.y = rigid_body.velocity.y.abs() * restitution;
+                },
+                EdgeBehavior::Kill => killed = true,
+                EdgeBehavior::Wrap => rigid_body.position.y += self.height(),
+            }
+        } else if past_top(self.top, rigid_body.position.y) {
+            match self.top {
+                EdgeBehavior::Open => {},
+                EdgeBehavior::Wall => {
+                    rigid_body.position.y = self.max.y - hy;
+                    rigid_body.velocity.y = -rigid_body.velocity.y.abs() * restitution;
+                },
+                EdgeBehavior::Kill => killed = true,
+                EdgeBehavior::Wrap => rigid_body.position.y -= self.height(),
+            }
+        }
+
+        if killed {
+            rigid_body.position = self.spawn_point.clone();
+            rigid_body.velocity = Vec2::new(0., 0.);
+            rigid_body.angular_velocity = 0.;
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use gamelibrary::{collider::Collider, rigid_body::RigidBodyType};
+
+    use super::*;
+
+    fn body(position: Vec2, velocity: Vec2, restitution: f32) -> RigidBody {
+        RigidBody {
+            position,
+            rotation: 0.,
+            angular_velocity: 0.,
+            velocity,
+            body_type: RigidBodyType::Dynamic,
+            owner: "host".to_string(),
+            collider: Collider {
+                hx: 10.,
+                hy: 10.,
+                restitution,
+                mass: 10.,
+                owner: "host".to_string()
+            }
+        }
+    }
+
+    fn bounds(behavior: EdgeBehavior) -> WorldBounds {
+        WorldBounds {
+            left: behavior,
+            right: behavior,
+            bottom: behavior,
+            top: behavior,
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn walls_push_bodies_back_in_and_bounce_them_by_their_restitution() {
+
+        let mut rigid_body = body(Vec2::new(5., 300.), Vec2::new(-200., 0.), 0.5);
+
+        bounds(EdgeBehavior::Wall).enforce(&mut rigid_body);
+
+        assert_eq!(rigid_body.position.x, 10.);
+        assert_eq!(rigid_body.velocity.x, 100.);
+
+        let mut rigid_body = body(Vec2::new(300., 715.), Vec2::new(0., 50.), 0.);
+
+        bounds(EdgeBehavior::Wall).enforce(&mut rigid_body);
+
+        assert_eq!(rigid_body.position.y, 710.);
+        assert_eq!(rigid_body.velocity.y, 0.);
+    }
+
+    #[test]
+    fn bodies_inside_are_left_alone() {
+
+        let mut rigid_body = body(Vec2::new(300., 300.), Vec2::new(-200., 80.), 1.);
+
+        bounds(EdgeBehavior::Wall).enforce(&mut rigid_body);
+
+        assert_eq!((rigid_body.position.x, rigid_body.position.y), (300., 300.));
+        assert_eq!((rigid_body.velocity.x, rigid_body.velocity.y), (-200., 80.));
+    }
+
+    #[test]
+    fn wrapping_comes_out_the_other_side() {
+
+        let mut rigid_body = body(Vec2::new(1285., -5.), Vec2::new(100., -100.), 0.);
+
+        bounds(EdgeBehavior::Wrap).enforce(&mut rigid_body);
+
+        assert_eq!((rigid_body.position.x, rigid_body.position.y), (5., 715.));
+        assert_eq!((rigid_body.velocity.x, rigid_body.velocity.y), (100., -100.));
+    }
+
+    #[test]
+    fn killed_bodies_go_back_to_the_spawn_point() {
+
+        let mut rigid_body = body(Vec2::new(300., -5.), Vec2::new(0., -500.), 0.);
+
+        bounds(EdgeBehavior::Kill).enforce(&mut rigid_body);
+
+        assert_eq!((rigid_body.position.x, rigid_body.position.y), (640., 500.));
+        assert_eq!((rigid_body.velocity.x, rigid_body.velocity.y), (0., 0.));
+    }
+
+    #[test]
+    fn open_edges_dont_stop_anything() {
+
+        let mut rigid_body = body(Vec2::new(-500., -500.), Vec2::new(-10., -10.), 0.);
+
+        bounds(EdgeBehavior::Open).enforce(&mut rigid_body);
+
+        assert_eq!((rigid_body.position.x, rigid_body.position.y), (-500., -500.));
+    }
+
+    #[test]
+    fn fitting_around_an_area_keeps_the_edges_and_moves_the_spawn_point_inside() {
+
+        let fitted = bounds(EdgeBehavior::Kill).around(&Vec2::new(2000., 0.), &Vec2::new(3000., 400.), 100.);
+
+        assert_eq!((fitted.min.x, fitted.min.y), (1900., -100.));
+        assert_eq!((fitted.max.x, fitted.max.y), (3100., 500.));
+        assert!(fitted.left == EdgeBehavior::Kill && fitted.top == EdgeBehavior::Kill);
+        assert_eq!((fitted.spawn_point.x, fitted.spawn_point.y), (2500., 450.));
+
+        let fitted = bounds(EdgeBehavior::Wall).around(&Vec2::new(0., 0.), &Vec2::new(2000., 1000.), 0.);
+
+        assert_eq!((fitted.spawn_point.x, fitted.spawn_point.y), (640., 500.));
+    }
+}